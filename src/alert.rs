@@ -0,0 +1,527 @@
+use std::{fmt, str::FromStr, time::Duration};
+
+use serde::Deserialize;
+
+use crate::{
+    history::MetricKey,
+    hook::{HooksConfig, Webhook},
+    layout::Panel,
+    metrics::Metrics,
+};
+
+/// How serious an alert is, also its color.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum Severity {
+    Info,
+    #[default]
+    Warning,
+    Critical,
+}
+
+impl Severity {
+    pub fn label(self) -> &'static str {
+        match self {
+            Severity::Info => "info",
+            Severity::Warning => "warning",
+            Severity::Critical => "critical",
+        }
+    }
+}
+
+/// Thermal pressure levels reported by `powermetrics`, from the coolest.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Pressure {
+    Nominal,
+    Moderate,
+    Heavy,
+    Trapping,
+    Sleeping,
+}
+
+impl Pressure {
+    const ALL: [Pressure; 5] = [
+        Pressure::Nominal,
+        Pressure::Moderate,
+        Pressure::Heavy,
+        Pressure::Trapping,
+        Pressure::Sleeping,
+    ];
+}
+
+impl FromStr for Pressure {
+    type Err = String;
+
+    fn from_str(text: &str) -> Result<Self, Self::Err> {
+        match text.to_ascii_lowercase().as_str() {
+            "nominal" => Ok(Pressure::Nominal),
+            "moderate" => Ok(Pressure::Moderate),
+            "heavy" => Ok(Pressure::Heavy),
+            "trapping" => Ok(Pressure::Trapping),
+            "sleeping" => Ok(Pressure::Sleeping),
+            _ => Err(format!("unknown thermal pressure `{text}`")),
+        }
+    }
+}
+
+/// Comparison of a rule.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Op {
+    Gt,
+    Ge,
+    Lt,
+    Le,
+    Eq,
+    Ne,
+}
+
+impl Op {
+    /// Does `value` compare to `threshold`, moved by `slack` towards clearing?
+    ///
+    /// A positive slack keeps an active alert until the value goes back past the threshold by
+    /// that much. `==` and `!=` take no slack, see [`AlertRule::hysteresis`].
+    fn holds(self, value: f64, threshold: f64, slack: f64) -> bool {
+        match self {
+            Op::Gt => value > threshold - slack,
+            Op::Ge => value >= threshold - slack,
+            Op::Lt => value < threshold + slack,
+            Op::Le => value <= threshold + slack,
+            Op::Eq => value == threshold,
+            Op::Ne => value != threshold,
+        }
+    }
+}
+
+impl FromStr for Op {
+    type Err = String;
+
+    fn from_str(text: &str) -> Result<Self, Self::Err> {
+        match text {
+            ">" => Ok(Op::Gt),
+            ">=" => Ok(Op::Ge),
+            "<" => Ok(Op::Lt),
+            "<=" => Ok(Op::Le),
+            "==" | "=" => Ok(Op::Eq),
+            "!=" => Ok(Op::Ne),
+            _ => Err(format!("unknown comparison `{text}`")),
+        }
+    }
+}
+
+/// Value of a process compared by a rule.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ProcessField {
+    /// CPU usage in percent of one core.
+    Cpu,
+    EnergyImpact,
+}
+
+/// What a rule compares.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Condition {
+    /// A recorded metric, e.g. `package_w > 25`.
+    Metric { key: MetricKey, op: Op, threshold: f64 },
+    /// The thermal pressure level, e.g. `thermal != Nominal`.
+    Thermal { op: Op, level: Pressure },
+    /// A process, summed over its pids, e.g. `process "node" cpu > 80%`.
+    Process { name: String, field: ProcessField, op: Op, threshold: f64 },
+}
+
+impl Condition {
+    /// Current value in `metrics`, `None` if there is nothing to compare, e.g. the process is not
+    /// running.
+    fn value(&self, metrics: &Metrics) -> Option<f64> {
+        match self {
+            Condition::Metric { key, .. } => Some(key.value(metrics)),
+            Condition::Thermal { .. } => {
+                metrics.thermal_pressure.parse::<Pressure>().ok().map(|level| level as u8 as f64)
+            },
+            Condition::Process { name, field, .. } => {
+                let mut matching = metrics.processes.iter().filter(|p| &p.name == name).peekable();
+                matching.peek()?;
+                Some(
+                    matching
+                        .map(|process| match field {
+                            ProcessField::Cpu => process.cpu_percent(),
+                            ProcessField::EnergyImpact => process.energy_impact,
+                        })
+                        .sum(),
+                )
+            },
+        }
+    }
+
+    fn op(&self) -> Op {
+        match *self {
+            Condition::Metric { op, .. }
+            | Condition::Thermal { op, .. }
+            | Condition::Process { op, .. } => op,
+        }
+    }
+
+    /// Is the value compared with `==` or `!=`, which no slack applies to?
+    fn is_equality(&self) -> bool {
+        matches!(self.op(), Op::Eq | Op::Ne)
+    }
+
+    /// Does `value` meet the condition, with `slack` as in [`Op::holds`]?
+    fn holds(&self, value: f64, slack: f64) -> bool {
+        match *self {
+            Condition::Metric { op, threshold, .. } | Condition::Process { op, threshold, .. } => {
+                op.holds(value, threshold, slack)
+            },
+            Condition::Thermal { op, level } => op.holds(value, level as u8 as f64, slack),
+        }
+    }
+
+    /// `value` formatted with its unit.
+    fn format(&self, value: f64) -> String {
+        match self {
+            Condition::Metric { key, .. } => format!("{value:.2} {}", key.unit()),
+            Condition::Thermal { .. } => format!("{:?}", Pressure::ALL[value as usize]),
+            Condition::Process { field: ProcessField::Cpu, .. } => format!("{value:.1} %"),
+            Condition::Process { field: ProcessField::EnergyImpact, .. } => format!("{value:.1}"),
+        }
+    }
+
+    /// Panels showing the compared value, highlighted while the alert is active.
+    pub fn panels(&self) -> &'static [Panel] {
+        match self {
+            Condition::Metric { key, .. } => match key {
+                MetricKey::ECpuActive => &[Panel::ECpu, Panel::CpuClusters, Panel::CpuChart],
+                MetricKey::PCpuActive => &[Panel::PCpu, Panel::CpuClusters, Panel::CpuChart],
+                MetricKey::ECpuFreq => &[Panel::ECpu, Panel::CpuClusters, Panel::FreqChart],
+                MetricKey::PCpuFreq => &[Panel::PCpu, Panel::CpuClusters, Panel::FreqChart],
+                MetricKey::GpuActive => &[Panel::Gpu, Panel::GpuInfo, Panel::GpuChart],
+                MetricKey::GpuFreq => &[Panel::Gpu, Panel::GpuInfo, Panel::FreqChart],
+                MetricKey::AnePower => &[Panel::Ane, Panel::PowerList, Panel::RailsChart],
+                MetricKey::CpuPower | MetricKey::GpuPower | MetricKey::PackagePower => {
+                    &[Panel::PowerList, Panel::PowerChart, Panel::RailsChart]
+                },
+                MetricKey::MemoryUsed | MetricKey::SwapUsed => &[Panel::Memory, Panel::MemoryChart],
+                MetricKey::NetInBytes
+                | MetricKey::NetOutBytes
+                | MetricKey::NetInPackets
+                | MetricKey::NetOutPackets => &[Panel::NetDisk, Panel::NetChart],
+                MetricKey::DiskReadOps
+                | MetricKey::DiskWriteOps
+                | MetricKey::DiskReadKBytes
+                | MetricKey::DiskWriteKBytes => &[Panel::NetDisk, Panel::DiskChart],
+            },
+            Condition::Thermal { .. } => &[Panel::SocInfo],
+            Condition::Process { .. } => &[Panel::Processes],
+        }
+    }
+}
+
+/// A condition that must hold for some time, parsed from text like `package_w > 25 for 10s`.
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(try_from = "String")]
+pub struct Trigger {
+    pub condition: Condition,
+    /// Seconds the condition must hold before the alert fires.
+    pub duration: f64,
+    text: String,
+}
+
+impl FromStr for Trigger {
+    type Err = String;
+
+    fn from_str(text: &str) -> Result<Self, Self::Err> {
+        let tokens = tokenize(text)?;
+        let (condition, rest) = match tokens.as_slice() {
+            [subject, op, level, rest @ ..] if subject == "thermal" => {
+                (Condition::Thermal { op: op.parse()?, level: level.parse()? }, rest)
+            },
+            [subject, name, field, op, value, rest @ ..] if subject == "process" => {
+                let field = match field.as_str() {
+                    "cpu" => ProcessField::Cpu,
+                    "energy" | "energy_impact" => ProcessField::EnergyImpact,
+                    _ => return Err(format!("unknown process value `{field}`, use cpu or energy")),
+                };
+                let unit = match field {
+                    ProcessField::Cpu => "%",
+                    ProcessField::EnergyImpact => "",
+                };
+                let threshold = parse_value(value, unit)?;
+                (Condition::Process { name: name.clone(), field, op: op.parse()?, threshold }, rest)
+            },
+            [name, op, value, rest @ ..] => {
+                let key = MetricKey::ALL
+                    .into_iter()
+                    .find(|key| key.name() == name)
+                    .ok_or_else(|| format!("unknown metric `{name}`"))?;
+                let threshold = parse_value(value, key.unit())?;
+                (Condition::Metric { key, op: op.parse()?, threshold }, rest)
+            },
+            _ => {
+                return Err(format!(
+                    "invalid rule `{text}`, expected e.g. `package_w > 25 for 10s`"
+                ))
+            },
+        };
+        let duration = match rest {
+            [] => 0.0,
+            [keyword, duration] if keyword == "for" => parse_duration(duration)?,
+            _ => return Err(format!("unexpected `{}` in rule `{text}`", rest.join(" "))),
+        };
+        Ok(Self { condition, duration, text: text.to_string() })
+    }
+}
+
+impl TryFrom<String> for Trigger {
+    type Error = String;
+
+    fn try_from(text: String) -> Result<Self, Self::Error> {
+        text.parse()
+    }
+}
+
+impl fmt::Display for Trigger {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(&self.text)
+    }
+}
+
+/// Splits a rule into words, a double-quoted name being one word.
+fn tokenize(text: &str) -> Result<Vec<String>, String> {
+    let mut tokens = vec![];
+    let mut rest = text.trim_start();
+    while !rest.is_empty() {
+        if let Some(quoted) = rest.strip_prefix('"') {
+            let (token, after) =
+                quoted.split_once('"').ok_or_else(|| format!("unclosed quote in rule `{text}`"))?;
+            tokens.push(token.to_string());
+            rest = after;
+        } else {
+            let end = rest.find(char::is_whitespace).unwrap_or(rest.len());
+            tokens.push(rest[..end].to_string());
+            rest = &rest[end..];
+        }
+        rest = rest.trim_start();
+    }
+    Ok(tokens)
+}
+
+/// Parses a number with an optional unit, converting sizes like `2GB` to `unit`.
+fn parse_value(text: &str, unit: &str) -> Result<f64, String> {
+    let split =
+        text.find(|c: char| !(c.is_ascii_digit() || c == '.' || c == '-')).unwrap_or(text.len());
+    let (number, suffix) = text.split_at(split);
+    let number: f64 = number.parse().map_err(|_| format!("invalid number `{text}`"))?;
+    if suffix.is_empty() || suffix.eq_ignore_ascii_case(unit) {
+        return Ok(number);
+    }
+    let bytes = match suffix.to_ascii_uppercase().as_str() {
+        "B" => 1.0,
+        "KB" => 1024.0,
+        "MB" => 1024.0 * 1024.0,
+        "GB" => 1024.0 * 1024.0 * 1024.0,
+        "TB" => 1024.0 * 1024.0 * 1024.0 * 1024.0,
+        _ => return Err(format!("unit `{suffix}` of `{text}` does not apply, expected {unit}")),
+    };
+    let per_unit = match unit {
+        "bytes/s" => 1.0,
+        "KBytes/s" => 1024.0,
+        "GB" => 1024.0 * 1024.0 * 1024.0,
+        _ => return Err(format!("unit `{suffix}` of `{text}` does not apply, expected {unit}")),
+    };
+    Ok(number * bytes / per_unit)
+}
+
+/// Parses a duration like `30s`, `5m`, `1h` or plain seconds.
+fn parse_duration(text: &str) -> Result<f64, String> {
+    let (number, scale) = match text.char_indices().last() {
+        Some((index, 's')) => (&text[..index], 1.0),
+        Some((index, 'm')) => (&text[..index], 60.0),
+        Some((index, 'h')) => (&text[..index], 3600.0),
+        _ => (text, 1.0),
+    };
+    match number.parse::<f64>() {
+        Ok(number) if number >= 0.0 => Ok(number * scale),
+        _ => Err(format!("invalid duration `{text}`")),
+    }
+}
+
+/// One rule of the `[[alerts.rules]]` config.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct AlertRule {
+    /// Shown in the alert list instead of the condition.
+    pub name: Option<String>,
+    pub when: Trigger,
+    #[serde(default)]
+    pub severity: Severity,
+    /// How far past the threshold the value must go back for the alert to clear.
+    ///
+    /// Rules comparing with `==` or `!=` have no margin to clear by, their alerts clear once the
+    /// condition stopped holding for the duration of the trigger instead.
+    #[serde(default)]
+    pub hysteresis: f64,
+    /// Run when this alert fires or resolves, after the hooks of every alert.
+    pub command: Option<String>,
+    pub webhook: Option<Webhook>,
+}
+
+impl AlertRule {
+    /// Name shown in the alert list.
+    pub fn title(&self) -> String {
+        self.name.clone().unwrap_or_else(|| self.when.to_string())
+    }
+}
+
+/// `[alerts]` section of the config.
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct AlertsConfig {
+    /// Ring the terminal bell when an alert fires.
+    pub bell: bool,
+    pub rules: Vec<AlertRule>,
+    pub hooks: HooksConfig,
+}
+
+impl AlertsConfig {
+    /// Checks that no rule sets a hysteresis it can't use, and that hooks get a usable timeout.
+    pub fn validate(&self) -> Result<(), String> {
+        let unusable =
+            |rule: &&AlertRule| rule.hysteresis != 0.0 && rule.when.condition.is_equality();
+        if let Some(rule) = self.rules.iter().find(unusable) {
+            return Err(format!(
+                "rule `{}` compares with == or !=, which hysteresis doesn't apply to: use `for` to \
+                 debounce it",
+                rule.when
+            ));
+        }
+        let timeout = self.hooks.timeout;
+        if timeout <= 0.0 || Duration::try_from_secs_f64(timeout).is_err() {
+            return Err(format!(
+                "invalid hooks timeout {timeout}, expected a positive number of seconds"
+            ));
+        }
+        Ok(())
+    }
+}
+
+/// An alert whose condition holds.
+#[derive(Debug, Clone)]
+pub struct Alert {
+    /// Index of the rule in the config.
+    pub rule: usize,
+    pub title: String,
+    pub severity: Severity,
+    /// When the alert fired, in seconds since the Unix epoch.
+    pub since: f64,
+    /// Value that fired it, with its unit.
+    pub value: String,
+    pub panels: &'static [Panel],
+}
+
+/// What happened to an alert.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Change {
+    /// Its condition started to hold.
+    Fired,
+    /// Its condition stopped holding.
+    Resolved,
+}
+
+impl Change {
+    pub fn label(self) -> &'static str {
+        match self {
+            Change::Fired => "fired",
+            Change::Resolved => "resolved",
+        }
+    }
+}
+
+/// Evaluation state of one rule.
+#[derive(Debug, Clone, Default)]
+struct RuleState {
+    /// When the condition started to hold.
+    holding_since: Option<f64>,
+    /// When the condition of an active `==` or `!=` alert stopped holding.
+    failing_since: Option<f64>,
+    active: Option<Alert>,
+}
+
+/// Rules evaluated on every snapshot of a host.
+#[derive(Debug, Clone, Default)]
+pub struct Alerts {
+    rules: Vec<AlertRule>,
+    states: Vec<RuleState>,
+}
+
+impl Alerts {
+    pub fn new(rules: &[AlertRule]) -> Self {
+        Self { rules: rules.to_vec(), states: vec![RuleState::default(); rules.len()] }
+    }
+
+    /// The same rules, none of them holding yet.
+    pub fn cleared(&self) -> Self {
+        Self::new(&self.rules)
+    }
+
+    /// Evaluates every rule on a new snapshot, returning the alerts that fired or resolved.
+    pub fn update(&mut self, metrics: &Metrics) -> Vec<(Change, Alert)> {
+        let mut changes = vec![];
+        for (index, (rule, state)) in self.rules.iter().zip(&mut self.states).enumerate() {
+            let condition = &rule.when.condition;
+            let slack = if state.active.is_some() { rule.hysteresis } else { 0.0 };
+            let current = condition.value(metrics);
+            let Some(value) = current.filter(|&value| condition.holds(value, slack)) else {
+                // Equality has no slack, keep the alert until it failed for as long as it had to
+                // hold, so it doesn't flap at the boundary.
+                if state.active.is_some() && condition.is_equality() {
+                    let failing_since = *state.failing_since.get_or_insert(metrics.timestamp);
+                    if metrics.timestamp - failing_since < rule.when.duration {
+                        continue;
+                    }
+                }
+                if let Some(mut alert) = state.active.take() {
+                    if let Some(value) = current {
+                        alert.value = condition.format(value);
+                    }
+                    changes.push((Change::Resolved, alert));
+                }
+                *state = RuleState::default();
+                continue;
+            };
+            state.failing_since = None;
+            let holding_since = *state.holding_since.get_or_insert(metrics.timestamp);
+            match &mut state.active {
+                Some(alert) => alert.value = condition.format(value),
+                None if metrics.timestamp - holding_since >= rule.when.duration => {
+                    let alert = Alert {
+                        rule: index,
+                        title: rule.title(),
+                        severity: rule.severity,
+                        since: metrics.timestamp,
+                        value: condition.format(value),
+                        panels: condition.panels(),
+                    };
+                    changes.push((Change::Fired, alert.clone()));
+                    state.active = Some(alert);
+                },
+                None => {},
+            }
+        }
+        changes
+    }
+
+    /// Alerts whose condition holds, most severe first.
+    pub fn active(&self) -> Vec<&Alert> {
+        let mut active: Vec<&Alert> =
+            self.states.iter().filter_map(|state| state.active.as_ref()).collect();
+        active.sort_by(|a, b| b.severity.cmp(&a.severity).then(a.since.total_cmp(&b.since)));
+        active
+    }
+
+    /// Highest severity of the alerts shown by `panel`.
+    pub fn severity(&self, panel: Panel) -> Option<Severity> {
+        self.active()
+            .into_iter()
+            .filter(|alert| alert.panels.contains(&panel))
+            .map(|alert| alert.severity)
+            .max()
+    }
+}
@@ -1,36 +1,497 @@
-use std::error;
+use std::{
+    cmp::Ordering,
+    error, fs,
+    path::{Path, PathBuf},
+    time::{Duration, Instant},
+};
 
-use crate::metrics::Metrics;
+use ratatui::layout::{Position, Rect};
+
+use crate::{
+    alert::{Alerts, Change},
+    config::Config,
+    hook::{Hooks, Notification},
+    host::Host,
+    keymap::Keymap,
+    layout::{Panel, Tab},
+    metrics::{Metrics, ProcessMetrics},
+    remote::ConnectionState,
+    stats::{ChartWindow, StatsWindow},
+    theme::Theme,
+};
 
 /// Application result type.
 pub type AppResult<T> = std::result::Result<T, Box<dyn error::Error>>;
 
+/// Longest time between the two clicks of a double-click.
+const DOUBLE_CLICK: Duration = Duration::from_millis(400);
+
+/// Column the process list is sorted by.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum ProcessSort {
+    #[default]
+    EnergyImpact,
+    Cpu,
+    Pid,
+    Name,
+}
+
+impl ProcessSort {
+    pub fn label(self) -> &'static str {
+        match self {
+            ProcessSort::EnergyImpact => "energy impact",
+            ProcessSort::Cpu => "CPU %",
+            ProcessSort::Pid => "PID",
+            ProcessSort::Name => "name",
+        }
+    }
+
+    /// The next column, wrapping around to energy impact.
+    pub fn next(self) -> Self {
+        match self {
+            ProcessSort::EnergyImpact => ProcessSort::Cpu,
+            ProcessSort::Cpu => ProcessSort::Pid,
+            ProcessSort::Pid => ProcessSort::Name,
+            ProcessSort::Name => ProcessSort::EnergyImpact,
+        }
+    }
+
+    /// Order of `a` and `b`, the highest values or the lowest PIDs and names first.
+    fn compare(self, a: &ProcessMetrics, b: &ProcessMetrics) -> Ordering {
+        match self {
+            ProcessSort::EnergyImpact => b.energy_impact.total_cmp(&a.energy_impact),
+            ProcessSort::Cpu => b.cpu_ms_per_s.total_cmp(&a.cpu_ms_per_s),
+            ProcessSort::Pid => a.pid.cmp(&b.pid),
+            ProcessSort::Name => a.name.to_lowercase().cmp(&b.name.to_lowercase()),
+        }
+    }
+}
+
 /// Application.
 #[derive(Debug)]
 pub struct App {
     /// Is the application running?
     pub running: bool,
 
-    /// counter
-    pub counter: u8,
+    /// Is the display frozen while sampling continues?
+    pub paused: bool,
+
+    /// Samples between the newest one and the one shown, 0 is live.
+    pub cursor: usize,
+
+    /// Monitored machines, never empty.
+    pub hosts: Vec<Host>,
+
+    /// Index of the selected host.
+    pub selected: usize,
+
+    /// Is the selected host shown in full instead of the multi-host dashboard?
+    pub drilled_down: bool,
+
+    /// View of the selected host.
+    pub tab: Tab,
+
+    /// Panels drawn in the last frame, for mouse hit-testing.
+    pub panels: Vec<(Panel, Rect)>,
+
+    /// Panel last clicked, drawn with a highlighted border.
+    pub focused: Option<Panel>,
+
+    /// Panel shown alone in the whole tab.
+    pub maximized: Option<Panel>,
+
+    /// Time and panel of the last click, to detect double-clicks.
+    last_click: Option<(Instant, Panel)>,
+
+    /// Position of the mouse pointer, for chart tooltips.
+    pub pointer: Option<(u16, u16)>,
+
+    /// Processes scrolled past in the process list.
+    pub process_offset: usize,
+
+    /// Column the process list is sorted by.
+    pub process_sort: ProcessSort,
+
+    /// Only processes whose name contains this, ignoring case, are listed.
+    pub process_filter: String,
+
+    /// Are keys typed into [`App::process_filter`]?
+    pub editing_filter: bool,
+
+    /// Outcome of the last export, shown in the header until the next key.
+    pub status: Option<String>,
+
+    /// Window of the statistics shown in panel titles and the stats table.
+    pub stats_window: StatsWindow,
+
+    /// Time span of the history charts.
+    pub chart_window: ChartWindow,
+
+    /// Is the stats table shown?
+    pub show_stats: bool,
 
-    /// metrics
-    pub metrics: Metrics,
+    /// Is the energy cost table shown?
+    pub show_energy: bool,
 
-    /// history
-    pub cpu_w: Vec<f64>,
+    /// Is the key bindings overlay shown?
+    pub show_help: bool,
+
+    /// Is the alert list shown?
+    pub show_alerts: bool,
+
+    /// Is the explanation of why `powermetrics` can't run shown?
+    pub show_degraded: bool,
+
+    /// Should the terminal bell ring, an alert fired since the last frame?
+    pub bell: bool,
+
+    /// Commands and webhooks run when alerts fire or resolve.
+    pub hooks: Hooks,
+
+    /// Keys of every action, built from the config.
+    pub keymap: Keymap,
+
+    /// Styles built from the config.
+    pub theme: Theme,
+
+    /// Settings.
+    pub config: Config,
 }
 
 impl Default for App {
     fn default() -> Self {
-        Self { running: true, counter: 0, metrics: Metrics::new(), cpu_w: vec![] }
+        Self::new(Config::default())
     }
 }
 
 impl App {
     /// Constructs a new instance of [`App`].
-    pub fn new() -> Self {
-        Self { metrics: Metrics::new(), ..Default::default() }
+    pub fn new(config: Config) -> Self {
+        Self {
+            running: true,
+            paused: false,
+            cursor: 0,
+            hosts: vec![Host {
+                alerts: Alerts::new(&config.alerts.rules),
+                ..Host::local(config.history.retention)
+            }],
+            selected: 0,
+            drilled_down: false,
+            tab: Tab::default(),
+            panels: vec![],
+            focused: None,
+            maximized: None,
+            last_click: None,
+            pointer: None,
+            process_offset: 0,
+            process_sort: ProcessSort::default(),
+            process_filter: String::new(),
+            editing_filter: false,
+            status: None,
+            stats_window: StatsWindow::default(),
+            chart_window: config.charts.window,
+            show_stats: false,
+            show_energy: false,
+            show_help: false,
+            show_alerts: false,
+            show_degraded: false,
+            bell: false,
+            hooks: Hooks::new(&config.alerts.hooks),
+            // Conflicts are reported when the config is loaded.
+            keymap: Keymap::new(&config.keys).unwrap_or_default(),
+            theme: Theme::from_config(&config.theme),
+            config,
+        }
+    }
+
+    /// Constructs an [`App`] rendering the metrics of the agents at `addrs`.
+    pub fn remote(addrs: Vec<String>, config: Config) -> Self {
+        let retention = config.history.retention;
+        let hosts = addrs
+            .into_iter()
+            .map(|addr| Host {
+                alerts: Alerts::new(&config.alerts.rules),
+                ..Host::remote(addr, retention)
+            })
+            .collect();
+        Self { hosts, ..Self::new(config) }
+    }
+
+    /// Opens the on-disk store of every host if enabled, reloading their recent history.
+    ///
+    /// A missing or unwritable directory must not keep the monitor from starting: hosts whose
+    /// store fails to open run without one, and the errors are returned to be reported.
+    pub fn open_stores(&mut self) -> Vec<String> {
+        if !self.config.store.enabled {
+            return vec![];
+        }
+        let config = &self.config.store;
+        self.hosts
+            .iter_mut()
+            .filter_map(|host| {
+                let err = host.open_store(config).err()?;
+                Some(format!("history of {} won't be saved: {err}", host.name()))
+            })
+            .collect()
+    }
+
+    /// The selected host.
+    pub fn host(&self) -> &Host {
+        &self.hosts[self.selected]
+    }
+
+    /// Is the multi-host dashboard shown?
+    pub fn is_dashboard(&self) -> bool {
+        self.hosts.len() > 1 && !self.drilled_down
+    }
+
+    /// Stores a new snapshot of the host at `index`.
+    pub fn on_metrics(&mut self, index: usize, metrics: Metrics) {
+        // Explain once why most panels are empty, as soon as it happens.
+        let degraded = metrics.degraded.is_some()
+            && self.hosts.get(index).is_some_and(|host| host.metrics.degraded.is_none());
+        if degraded && index == self.selected && !self.has_overlay() {
+            self.show_degraded = true;
+        }
+        if let Some(host) = self.hosts.get_mut(index) {
+            for (change, alert) in host.on_metrics(metrics) {
+                self.bell |= change == Change::Fired && self.config.alerts.bell;
+                let rule = &self.config.alerts.rules[alert.rule];
+                let time = host.metrics.timestamp;
+                let notification = Notification::new(host.name(), change, &alert, rule, time);
+                self.hooks.notify(notification, alert.rule, rule);
+            }
+        }
+        // Keep showing the same moment while paused or scrubbing.
+        if index == self.selected && !self.is_live() {
+            self.cursor = (self.cursor + 1).min(self.host().history.len().saturating_sub(1));
+        }
+    }
+
+    /// Records a connection change of the host at `index`.
+    pub fn on_connection(&mut self, index: usize, state: ConnectionState) {
+        if let Some(host) = self.hosts.get_mut(index) {
+            host.connection = state;
+        }
+    }
+
+    /// Selects the next host in the dashboard.
+    pub fn select_next(&mut self) {
+        self.selected = (self.selected + 1) % self.hosts.len();
+        self.go_live();
+    }
+
+    /// Selects the previous host in the dashboard.
+    pub fn select_previous(&mut self) {
+        self.selected = (self.selected + self.hosts.len() - 1) % self.hosts.len();
+        self.go_live();
+    }
+
+    /// Shows the selected host in full.
+    pub fn drill_down(&mut self) {
+        self.drilled_down = true;
+        self.go_live();
+    }
+
+    /// Is a single host of the dashboard shown in full?
+    pub fn can_go_back(&self) -> bool {
+        self.hosts.len() > 1 && self.drilled_down
+    }
+
+    /// Goes back from a single host to the dashboard.
+    pub fn back(&mut self) {
+        self.drilled_down = false;
+    }
+
+    /// Shows `tab` of the selected host.
+    pub fn select_tab(&mut self, tab: Tab) {
+        self.tab = tab;
+        self.focused = None;
+        self.maximized = None;
+    }
+
+    /// Shows the next tab.
+    pub fn next_tab(&mut self) {
+        self.select_tab(self.tab.next());
+    }
+
+    /// Shows the previous tab.
+    pub fn previous_tab(&mut self) {
+        self.select_tab(self.tab.previous());
+    }
+
+    /// Panel drawn at `column` and `row` in the last frame.
+    pub fn panel_at(&self, column: u16, row: u16) -> Option<Panel> {
+        let position = Position { x: column, y: row };
+        self.panels.iter().find(|(_, area)| area.contains(position)).map(|&(panel, _)| panel)
+    }
+
+    /// Focuses the panel clicked at `now`, maximizing or restoring it on a double-click.
+    pub fn click(&mut self, panel: Panel, now: Instant) {
+        match self.last_click {
+            Some((time, last)) if last == panel && now.duration_since(time) <= DOUBLE_CLICK => {
+                self.toggle_maximize(panel);
+                self.last_click = None;
+            },
+            _ => self.last_click = Some((now, panel)),
+        }
+        self.focused = Some(panel);
+    }
+
+    /// Shows `panel` alone, or the whole tab again if it already is.
+    pub fn toggle_maximize(&mut self, panel: Panel) {
+        self.maximized = match self.maximized {
+            Some(maximized) if maximized == panel => None,
+            _ => Some(panel),
+        };
+    }
+
+    /// Shows the whole tab again after a panel was maximized.
+    pub fn restore(&mut self) {
+        self.maximized = None;
+    }
+
+    /// Processes of `metrics` matching the filter, in the sort order.
+    pub fn processes<'a>(&self, metrics: &'a Metrics) -> Vec<&'a ProcessMetrics> {
+        let filter = self.process_filter.to_lowercase();
+        let mut processes: Vec<_> = metrics
+            .processes
+            .iter()
+            .filter(|process| process.name.to_lowercase().contains(&filter))
+            .collect();
+        processes.sort_by(|a, b| self.process_sort.compare(a, b));
+        processes
+    }
+
+    /// Scrolls the process list by `rows`, down when positive.
+    pub fn scroll_processes(&mut self, rows: isize) {
+        let count = self.processes(&self.host().metrics).len();
+        self.process_offset =
+            self.process_offset.saturating_add_signed(rows).min(count.saturating_sub(1));
+    }
+
+    /// Sorts the process list by the next column.
+    pub fn next_process_sort(&mut self) {
+        self.process_sort = self.process_sort.next();
+        self.process_offset = 0;
+    }
+
+    /// Starts typing a filter on process names.
+    pub fn edit_filter(&mut self) {
+        self.editing_filter = true;
+    }
+
+    /// Adds `char` to the process filter being typed.
+    pub fn push_filter(&mut self, char: char) {
+        self.process_filter.push(char);
+        self.process_offset = 0;
+    }
+
+    /// Removes the last character of the process filter being typed.
+    pub fn pop_filter(&mut self) {
+        self.process_filter.pop();
+    }
+
+    /// Stops typing, keeping the filter.
+    pub fn apply_filter(&mut self) {
+        self.editing_filter = false;
+    }
+
+    /// Stops typing and lists every process again.
+    pub fn clear_filter(&mut self) {
+        self.editing_filter = false;
+        self.process_filter.clear();
+    }
+
+    /// Saves the snapshot shown to the current directory, telling where in the header.
+    pub fn export(&mut self) {
+        self.status = Some(match self.export_to(Path::new(".")) {
+            Ok(path) => format!("saved {}", path.display()),
+            Err(err) => format!("export failed: {err}"),
+        });
+    }
+
+    /// Writes the snapshot shown as JSON to a new file in `dir`, returning its path.
+    pub fn export_to(&self, dir: &Path) -> AppResult<PathBuf> {
+        let host = self.host();
+        let metrics = if self.is_live() {
+            Some(&host.metrics).filter(|_| host.has_metrics())
+        } else {
+            host.history.snapshot(self.cursor)
+        };
+        let metrics = metrics.ok_or("no snapshot yet")?;
+        // Remote hosts are named by address, e.g. `mac-mini:7878`.
+        let safe = |char: char| char.is_alphanumeric() || char == '.' || char == '-';
+        let name: String =
+            host.name().chars().map(|char| if safe(char) { char } else { '_' }).collect();
+        let path = dir.join(format!("mactop-rs-{name}-{}.json", metrics.timestamp as u64));
+        fs::write(&path, serde_json::to_string_pretty(metrics)?)?;
+        Ok(path)
+    }
+
+    /// Switches to the next statistics window.
+    pub fn next_stats_window(&mut self) {
+        self.stats_window = self.stats_window.next();
+    }
+
+    /// Shows a shorter time span in the charts.
+    pub fn zoom_in(&mut self) {
+        self.chart_window = self.chart_window.zoom_in();
+    }
+
+    /// Shows a longer time span in the charts.
+    pub fn zoom_out(&mut self) {
+        self.chart_window = self.chart_window.zoom_out();
+    }
+
+    /// Shows or hides the stats table.
+    pub fn toggle_stats(&mut self) {
+        self.show_stats = !self.show_stats;
+        self.show_energy = false;
+        self.show_help = false;
+        self.show_alerts = false;
+        self.show_degraded = false;
+    }
+
+    /// Shows or hides the energy cost table.
+    pub fn toggle_energy(&mut self) {
+        self.show_energy = !self.show_energy;
+        self.show_stats = false;
+        self.show_help = false;
+        self.show_alerts = false;
+        self.show_degraded = false;
+    }
+
+    /// Shows or hides the key bindings.
+    pub fn toggle_help(&mut self) {
+        self.show_help = !self.show_help;
+        self.show_stats = false;
+        self.show_energy = false;
+        self.show_alerts = false;
+        self.show_degraded = false;
+    }
+
+    /// Shows or hides the alert list.
+    pub fn toggle_alerts(&mut self) {
+        self.show_alerts = !self.show_alerts;
+        self.show_stats = false;
+        self.show_energy = false;
+        self.show_help = false;
+        self.show_degraded = false;
+    }
+
+    /// Hides the explanation of why `powermetrics` can't run.
+    pub fn dismiss_degraded(&mut self) {
+        self.show_degraded = false;
+    }
+
+    /// Is a popup covering the panels?
+    pub fn has_overlay(&self) -> bool {
+        self.show_help
+            || self.show_stats
+            || self.show_energy
+            || self.show_alerts
+            || self.show_degraded
     }
 
     /// Set running to false to quit the application.
@@ -38,15 +499,35 @@ impl App {
         self.running = false;
     }
 
-    pub fn increment_counter(&mut self) {
-        if let Some(res) = self.counter.checked_add(1) {
-            self.counter = res;
-        }
+    /// Is the newest snapshot shown and updated as samples arrive?
+    pub fn is_live(&self) -> bool {
+        !self.paused && self.cursor == 0
     }
 
-    pub fn decrement_counter(&mut self) {
-        if let Some(res) = self.counter.checked_sub(1) {
-            self.counter = res;
+    /// Freezes the display, or goes back to live when already frozen.
+    pub fn toggle_pause(&mut self) {
+        if self.is_live() {
+            self.paused = true;
+        } else {
+            self.go_live();
         }
     }
+
+    /// Moves the cursor `steps` samples back in time, pausing the display.
+    pub fn scrub_back(&mut self, steps: usize) {
+        let oldest = self.host().history.len().saturating_sub(1);
+        self.cursor = (self.cursor + steps).min(oldest);
+        self.paused = true;
+    }
+
+    /// Moves the cursor `steps` samples forward in time, stopping at the newest sample.
+    pub fn scrub_forward(&mut self, steps: usize) {
+        self.cursor = self.cursor.saturating_sub(steps);
+    }
+
+    /// Shows the newest snapshot again.
+    pub fn go_live(&mut self) {
+        self.paused = false;
+        self.cursor = 0;
+    }
 }
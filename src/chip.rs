@@ -0,0 +1,76 @@
+use serde::Deserialize;
+
+/// Peak figures of a chip, the full scale of gauges and charts. `None` when unknown.
+///
+/// Also the `[chip]` section of the config, the figures of chips the built-in database doesn't
+/// know yet.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct ChipSpec {
+    /// Highest frequency of the efficiency cores.
+    pub e_freq_mhz: Option<u32>,
+    /// Highest frequency of the performance cores.
+    pub p_freq_mhz: Option<u32>,
+    /// Highest frequency of the GPU.
+    pub gpu_freq_mhz: Option<u32>,
+    /// GPU cores of the largest configuration.
+    pub gpu_cores: Option<u32>,
+    /// Neural engine power at full load, in watts.
+    pub ane_peak_w: Option<f64>,
+    /// Typical package power at full load, in watts.
+    pub tdp_w: Option<f64>,
+}
+
+/// Name, E and P core, and GPU frequencies in MHz, GPU cores, ANE peak and TDP in watts.
+///
+/// Frequencies are the highest states `powermetrics` reports, powers are approximate.
+const CHIPS: &[(&str, u32, u32, u32, u32, f64, f64)] = &[
+    ("M1", 2064, 3204, 1278, 8, 8.0, 20.0),
+    ("M1 Pro", 2064, 3228, 1296, 16, 8.0, 45.0),
+    ("M1 Max", 2064, 3228, 1296, 32, 8.0, 90.0),
+    ("M1 Ultra", 2064, 3228, 1296, 64, 16.0, 180.0),
+    ("M2", 2424, 3504, 1398, 10, 8.0, 22.0),
+    ("M2 Pro", 2424, 3504, 1398, 19, 8.0, 45.0),
+    ("M2 Max", 2424, 3696, 1398, 38, 8.0, 90.0),
+    ("M2 Ultra", 2424, 3696, 1398, 76, 16.0, 180.0),
+    ("M3", 2748, 4056, 1380, 10, 8.0, 22.0),
+    ("M3 Pro", 2748, 4056, 1380, 18, 8.0, 45.0),
+    ("M3 Max", 2748, 4056, 1380, 40, 8.0, 90.0),
+    ("M3 Ultra", 2748, 4056, 1380, 80, 16.0, 180.0),
+    ("M4", 2892, 4464, 1578, 10, 8.0, 22.0),
+    ("M4 Pro", 2592, 4512, 1578, 20, 8.0, 45.0),
+    ("M4 Max", 2592, 4512, 1578, 40, 8.0, 90.0),
+];
+
+impl ChipSpec {
+    /// Figures of the chip named in `model`, e.g. `Apple M2 Max` on macOS or
+    /// `Apple MacBook Air (M1, 2020)` under Asahi Linux.
+    pub fn lookup(model: &str) -> Option<Self> {
+        let words: Vec<&str> = model.split(|c: char| !c.is_alphanumeric()).collect();
+        // The longest match, so `M1 Pro` isn't taken for an `M1`.
+        CHIPS
+            .iter()
+            .filter(|(name, ..)| {
+                let name: Vec<&str> = name.split(' ').collect();
+                words.windows(name.len()).any(|window| window == name)
+            })
+            .max_by_key(|(name, ..)| name.len())
+            .map(|&(_, e_freq_mhz, p_freq_mhz, gpu_freq_mhz, gpu_cores, ane_peak_w, tdp_w)| Self {
+                e_freq_mhz: Some(e_freq_mhz),
+                p_freq_mhz: Some(p_freq_mhz),
+                gpu_freq_mhz: Some(gpu_freq_mhz),
+                gpu_cores: Some(gpu_cores),
+                ane_peak_w: Some(ane_peak_w),
+                tdp_w: Some(tdp_w),
+            })
+    }
+
+    /// The figures of the chip named in `model`, or those set in `self` if the database doesn't
+    /// know it.
+    ///
+    /// Known chips ignore the config, so with several hosts the figures set for an unknown chip
+    /// don't rescale the others.
+    pub fn resolve(&self, model: &str) -> Self {
+        Self::lookup(model).unwrap_or(*self)
+    }
+}
@@ -0,0 +1,93 @@
+use std::{env, path::PathBuf};
+
+use crate::app::AppResult;
+
+/// Default address the agent listens on, only reachable from this machine: serving other machines
+/// has to be asked for with `--listen`, as there is no authentication.
+pub const DEFAULT_ADDR: &str = "127.0.0.1:7878";
+
+const USAGE: &str = "\
+Usage:
+    mactop-rs                     monitor this machine
+    mactop-rs --remote HOST:PORT  monitor a machine running `mactop-rs agent`, repeat to
+                                  get a dashboard of several machines
+    mactop-rs agent [--listen ADDR]
+                                  collect metrics and serve them over TCP (default
+                                  127.0.0.1:7878, use --listen 0.0.0.0:7878 to serve other
+                                  machines, without any authentication)
+    mactop-rs agent --stdio       collect metrics and write them to stdout, used to run only
+                                  the collector as root
+
+Options:
+    --config PATH                 read settings from PATH instead of
+                                  ~/.config/mactop-rs/config.toml
+";
+
+/// What the process should do.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Mode {
+    /// Collect and render metrics of this machine.
+    Local,
+    /// Render metrics streamed by one or more remote agents.
+    Remote(Vec<String>),
+    /// Collect metrics and serve them to remote clients.
+    Agent(String),
+    /// Collect metrics and write them to stdout, for an interface started without root.
+    Helper,
+}
+
+/// Command line arguments.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Args {
+    pub mode: Mode,
+    /// Config file given with `--config`.
+    pub config: Option<PathBuf>,
+}
+
+impl Args {
+    /// Parses the arguments of the current process, exiting on `--help`.
+    pub fn from_env() -> AppResult<Self> {
+        match Self::parse(env::args().skip(1)) {
+            Ok(Some(args)) => Ok(args),
+            Ok(None) => {
+                print!("{USAGE}");
+                std::process::exit(0);
+            },
+            Err(msg) => Err(format!("{msg}\n\n{USAGE}").into()),
+        }
+    }
+
+    /// Parses `args` (without the program name). Returns `None` when help was requested.
+    pub fn parse<I: IntoIterator<Item = String>>(args: I) -> Result<Option<Self>, String> {
+        let mut args = args.into_iter();
+        let mut mode = Mode::Local;
+        let mut config = None;
+        while let Some(arg) = args.next() {
+            match arg.as_str() {
+                "-h" | "--help" => return Ok(None),
+                "agent" if mode == Mode::Local => mode = Mode::Agent(DEFAULT_ADDR.to_string()),
+                "--listen" if matches!(mode, Mode::Agent(_)) => {
+                    mode = Mode::Agent(value(&mut args, &arg)?);
+                },
+                "--stdio" if matches!(mode, Mode::Agent(_)) => mode = Mode::Helper,
+                "--remote" => {
+                    let addr = value(&mut args, &arg)?;
+                    match &mut mode {
+                        Mode::Local => mode = Mode::Remote(vec![addr]),
+                        Mode::Remote(addrs) => addrs.push(addr),
+                        Mode::Agent(_) | Mode::Helper => {
+                            return Err(format!("unexpected argument `{arg}`"))
+                        },
+                    }
+                },
+                "--config" => config = Some(PathBuf::from(value(&mut args, &arg)?)),
+                _ => return Err(format!("unexpected argument `{arg}`")),
+            }
+        }
+        Ok(Some(Self { mode, config }))
+    }
+}
+
+fn value<I: Iterator<Item = String>>(args: &mut I, flag: &str) -> Result<String, String> {
+    args.next().ok_or_else(|| format!("`{flag}` expects a value"))
+}
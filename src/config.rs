@@ -0,0 +1,190 @@
+use std::{
+    env, fs,
+    path::{Path, PathBuf},
+};
+
+use serde::Deserialize;
+
+use crate::{
+    alert::AlertsConfig,
+    app::AppResult,
+    chip::ChipSpec,
+    keymap::{Keymap, KeymapConfig},
+    layout::{CompactConfig, LayoutConfig},
+    privilege::PrivilegesConfig,
+    stats::ChartWindow,
+    theme::ThemeConfig,
+};
+
+/// Settings read from the TOML config file.
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Config {
+    pub history: HistoryConfig,
+    pub tariff: TariffConfig,
+    pub store: StoreConfig,
+    pub layout: LayoutConfig,
+    pub compact: CompactConfig,
+    pub theme: ThemeConfig,
+    pub keys: KeymapConfig,
+    pub charts: ChartsConfig,
+    pub alerts: AlertsConfig,
+    pub privileges: PrivilegesConfig,
+    /// Overrides of the built-in chip figures.
+    pub chip: ChipSpec,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct HistoryConfig {
+    /// Samples kept per metric, about one per second.
+    pub retention: usize,
+}
+
+impl Default for HistoryConfig {
+    fn default() -> Self {
+        Self { retention: 3600 }
+    }
+}
+
+/// How charts label their time axis.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum TimeAxis {
+    /// Seconds or minutes before the cursor, e.g. `-60s`.
+    #[default]
+    Relative,
+    /// Local wall-clock time, e.g. `14:03:27`.
+    Clock,
+}
+
+/// History charts.
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct ChartsConfig {
+    pub time_axis: TimeAxis,
+    /// Window shown on startup, zoomed with keys.
+    pub window: ChartWindow,
+}
+
+/// On-disk history, reloaded on startup. Off unless enabled, as it can take hundreds of MB.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct StoreConfig {
+    pub enabled: bool,
+    /// Defaults to `$XDG_DATA_HOME/mactop-rs/history`, falling back to `~/.local/share`. A
+    /// leading `~/` is the home directory.
+    pub dir: Option<PathBuf>,
+    /// A new segment is started when the current one reaches this size...
+    pub segment_bytes: u64,
+    /// ...or spans this many minutes.
+    pub segment_minutes: f64,
+    /// Segments older than this are deleted.
+    pub max_age_hours: f64,
+    /// The oldest segments are deleted while the store is larger than this.
+    pub max_bytes: u64,
+    /// Minutes of history loaded on startup.
+    pub reload_minutes: f64,
+}
+
+impl Default for StoreConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            dir: None,
+            segment_bytes: 8 << 20,
+            segment_minutes: 60.0,
+            max_age_hours: 7.0 * 24.0,
+            max_bytes: 512 << 20,
+            reload_minutes: 60.0,
+        }
+    }
+}
+
+impl StoreConfig {
+    /// Directory of the store.
+    pub fn dir(&self) -> Option<PathBuf> {
+        if let Some(dir) = &self.dir {
+            return match dir.strip_prefix("~") {
+                Ok(rest) => Some(PathBuf::from(env::var_os("HOME")?).join(rest)),
+                Err(_) => Some(dir.clone()),
+            };
+        }
+        let data = env::var_os("XDG_DATA_HOME").map(PathBuf::from).or_else(|| {
+            env::var_os("HOME").map(|home| PathBuf::from(home).join(".local").join("share"))
+        })?;
+        Some(data.join("mactop-rs").join("history"))
+    }
+}
+
+/// Price and carbon intensity of the electricity, used to estimate what the measured energy costs.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct TariffConfig {
+    /// Price of one kWh in `currency`.
+    pub price_per_kwh: f64,
+    /// Symbol printed before prices.
+    pub currency: String,
+    /// Grams of CO2 emitted per kWh by the grid.
+    pub carbon_g_per_kwh: f64,
+}
+
+impl Default for TariffConfig {
+    fn default() -> Self {
+        Self { price_per_kwh: 0.15, currency: "$".to_string(), carbon_g_per_kwh: 400.0 }
+    }
+}
+
+impl TariffConfig {
+    /// Price of `joules` of energy.
+    pub fn cost(&self, joules: f64) -> f64 {
+        joules / 3.6e6 * self.price_per_kwh
+    }
+
+    /// Grams of CO2 emitted for `joules` of energy.
+    pub fn emissions_g(&self, joules: f64) -> f64 {
+        joules / 3.6e6 * self.carbon_g_per_kwh
+    }
+
+    /// Formats the cost and emissions of `joules`, e.g. `$0.0012 · 3.2 g CO2`.
+    pub fn format(&self, joules: f64) -> String {
+        format!("{}{:.4} · {:.1} g CO2", self.currency, self.cost(joules), self.emissions_g(joules))
+    }
+}
+
+impl Config {
+    /// Loads the config from `path`, or from [`Config::default_path`] if it exists.
+    pub fn load(path: Option<&Path>) -> AppResult<Self> {
+        let path = match path {
+            Some(path) => path.to_path_buf(),
+            None => match Self::default_path() {
+                Some(path) if path.exists() => path,
+                _ => return Ok(Self::default()),
+            },
+        };
+        let text = fs::read_to_string(&path)
+            .map_err(|err| format!("failed to read {}: {err}", path.display()))?;
+        let config = Self::parse(&text)
+            .map_err(|err| format!("invalid config {}: {err}", path.display()))?;
+        config
+            .layout
+            .validate()
+            .and_then(|_| config.alerts.validate())
+            .and_then(|_| Keymap::new(&config.keys).map(|_| ()))
+            .map_err(|err| format!("invalid config {}: {err}", path.display()))?;
+        Ok(config)
+    }
+
+    /// Parses the TOML text of a config file.
+    pub fn parse(text: &str) -> Result<Self, toml::de::Error> {
+        toml::from_str(text)
+    }
+
+    /// `$XDG_CONFIG_HOME/mactop-rs/config.toml`, falling back to `~/.config`.
+    pub fn default_path() -> Option<PathBuf> {
+        let dir = env::var_os("XDG_CONFIG_HOME")
+            .map(PathBuf::from)
+            .or_else(|| env::var_os("HOME").map(|home| PathBuf::from(home).join(".config")))?;
+        Some(dir.join("mactop-rs").join("config.toml"))
+    }
+}
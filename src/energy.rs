@@ -0,0 +1,254 @@
+use std::collections::HashMap;
+
+use crate::{
+    config::TariffConfig,
+    history::{History, MetricKey},
+    metrics::Metrics,
+};
+
+/// Gaps between samples longer than this, e.g. while an agent was unreachable, are not integrated.
+const MAX_GAP_SECONDS: f64 = 30.0;
+
+/// A power rail reported by `powermetrics`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Rail {
+    Cpu,
+    Gpu,
+    Ane,
+    /// CPU + GPU + ANE.
+    Package,
+}
+
+impl Rail {
+    pub const ALL: [Rail; 4] = [Rail::Cpu, Rail::Gpu, Rail::Ane, Rail::Package];
+
+    pub fn label(self) -> &'static str {
+        match self {
+            Rail::Cpu => "CPU",
+            Rail::Gpu => "GPU",
+            Rail::Ane => "ANE",
+            Rail::Package => "Total",
+        }
+    }
+
+    /// The power metric of this rail.
+    pub fn key(self) -> MetricKey {
+        match self {
+            Rail::Cpu => MetricKey::CpuPower,
+            Rail::Gpu => MetricKey::GpuPower,
+            Rail::Ane => MetricKey::AnePower,
+            Rail::Package => MetricKey::PackagePower,
+        }
+    }
+
+    fn index(self) -> usize {
+        self as usize
+    }
+}
+
+/// Energy used per rail since the session started, integrated from the power samples.
+#[derive(Debug, Clone, Default)]
+pub struct EnergyMeter {
+    joules: [f64; 4],
+    /// Seconds covered by the integration.
+    elapsed: f64,
+    /// Time and power of every rail of the previous sample.
+    last: Option<(f64, [f64; 4])>,
+    /// Estimated joules per process name.
+    processes: HashMap<String, f64>,
+}
+
+impl EnergyMeter {
+    /// Adds the energy used between the previous sample and `metrics`, with the trapezoidal rule
+    /// over the real time between both timestamps.
+    ///
+    /// The package energy of the interval is split between processes by their share of the total
+    /// energy impact, which is only an estimate: `powermetrics` reports no per-process power.
+    pub fn record(&mut self, metrics: &Metrics) {
+        let watts = Rail::ALL.map(|rail| rail.key().value(metrics));
+        if let Some((last_time, last_watts)) = self.last {
+            let dt = metrics.timestamp - last_time;
+            if dt > 0.0 && dt <= MAX_GAP_SECONDS {
+                for rail in Rail::ALL {
+                    let i = rail.index();
+                    self.joules[i] += (last_watts[i] + watts[i]) / 2.0 * dt;
+                }
+                self.elapsed += dt;
+
+                let package = Rail::Package.index();
+                let joules = (last_watts[package] + watts[package]) / 2.0 * dt;
+                let total_impact: f64 = metrics.processes.iter().map(|p| p.energy_impact).sum();
+                if total_impact > 0.0 {
+                    for process in &metrics.processes {
+                        *self.processes.entry(process.name.clone()).or_default() +=
+                            joules * process.energy_impact / total_impact;
+                    }
+                }
+            }
+        }
+        self.last = Some((metrics.timestamp, watts));
+    }
+
+    /// The meter as it was at `time`, taking back the energy of the `history` samples after it.
+    ///
+    /// It only takes back, so it can't go further than the start of the session.
+    pub fn at(&self, history: &History, time: f64) -> EnergyMeter {
+        let mut meter = self.clone();
+        for rail in Rail::ALL {
+            let samples: Vec<_> = history.series(rail.key()).since(time).collect();
+            for pair in samples.windows(2) {
+                let dt = pair[1].time - pair[0].time;
+                if dt > 0.0 && dt <= MAX_GAP_SECONDS {
+                    meter.joules[rail.index()] -= (pair[0].value + pair[1].value) / 2.0 * dt;
+                    if rail == Rail::Package {
+                        meter.elapsed -= dt;
+                    }
+                }
+            }
+        }
+        let newest = history.series(MetricKey::PackagePower).last().map_or(time, |s| s.time);
+        for consumer in top_consumers(history, time, newest) {
+            if let Some(joules) = meter.processes.get_mut(&consumer.name) {
+                *joules -= consumer.joules;
+            }
+        }
+        meter.joules = meter.joules.map(|joules| joules.max(0.0));
+        meter.elapsed = meter.elapsed.max(0.0);
+        meter.processes.retain(|_, joules| *joules > 0.0);
+        meter
+    }
+
+    /// Energy used by `rail` in joules.
+    pub fn joules(&self, rail: Rail) -> f64 {
+        self.joules[rail.index()]
+    }
+
+    /// Energy used by `rail` in watt-hours.
+    pub fn watt_hours(&self, rail: Rail) -> f64 {
+        self.joules(rail) / 3600.0
+    }
+
+    /// Seconds of samples integrated so far.
+    pub fn elapsed(&self) -> f64 {
+        self.elapsed
+    }
+
+    /// Mean power of `rail` over the session.
+    pub fn average_watts(&self, rail: Rail) -> f64 {
+        if self.elapsed > 0.0 {
+            self.joules(rail) / self.elapsed
+        } else {
+            0.0
+        }
+    }
+
+    /// Estimated joules per process name, highest first.
+    pub fn processes(&self) -> Vec<(&str, f64)> {
+        let mut processes: Vec<(&str, f64)> =
+            self.processes.iter().map(|(name, &joules)| (name.as_str(), joules)).collect();
+        processes.sort_by(|a, b| b.1.total_cmp(&a.1));
+        processes
+    }
+
+    /// One line per rail and the top processes, printed when mactop-rs exits.
+    pub fn summary(&self, tariff: &TariffConfig) -> String {
+        let mut summary = format!("Energy over {}:\n", format_duration(self.elapsed));
+        for rail in Rail::ALL {
+            summary.push_str(&format!(
+                "  {:<6}{:>12.1} J {:>10.4} Wh {:>8.2} W avg  {}\n",
+                rail.label(),
+                self.joules(rail),
+                self.watt_hours(rail),
+                self.average_watts(rail),
+                tariff.format(self.joules(rail))
+            ));
+        }
+        let processes = self.processes();
+        if !processes.is_empty() {
+            summary.push_str("Top processes (estimated):\n");
+        }
+        for (name, joules) in processes.into_iter().take(5) {
+            summary.push_str(&format!("  {name:<24}{joules:>12.1} J  {}\n", tariff.format(joules)));
+        }
+        summary
+    }
+}
+
+/// A process ranked by the energy it used over a window of the history.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Consumer {
+    pub name: String,
+    /// Energy impact accumulated over the window, per second of samples.
+    pub energy_impact: f64,
+    /// Estimated joules, the package energy split by energy impact as in [`EnergyMeter`].
+    pub joules: f64,
+    /// Was the process running at the end of the window?
+    pub running: bool,
+}
+
+/// Processes that ran between `from` and `to` by estimated energy, highest first.
+///
+/// Processes that exited within the window are included, so a short burst still shows up.
+pub fn top_consumers(history: &History, from: f64, to: f64) -> Vec<Consumer> {
+    // Length and package energy of every interval ending within the window, by end time.
+    let package: Vec<_> = history.series(MetricKey::PackagePower).until(to).collect();
+    let mut intervals: HashMap<u64, (f64, f64)> = HashMap::new();
+    for pair in package.windows(2) {
+        let (last, sample) = (pair[0], pair[1]);
+        let dt = sample.time - last.time;
+        if sample.time > from && dt > 0.0 && dt <= MAX_GAP_SECONDS {
+            intervals.insert(sample.time.to_bits(), (dt, (last.value + sample.value) / 2.0 * dt));
+        }
+    }
+    let in_window = |time: f64| intervals.get(&time.to_bits());
+
+    let mut total_impact: HashMap<u64, f64> = HashMap::new();
+    for process in history.processes().values() {
+        for sample in process.energy_impact.since(from).filter(|s| in_window(s.time).is_some()) {
+            *total_impact.entry(sample.time.to_bits()).or_default() += sample.value;
+        }
+    }
+
+    let newest = package.last().map(|sample| sample.time);
+    let mut consumers: Vec<Consumer> = history
+        .processes()
+        .iter()
+        .map(|(name, process)| {
+            let mut consumer = Consumer {
+                name: name.clone(),
+                energy_impact: 0.0,
+                joules: 0.0,
+                running: process.energy_impact.until(to).next_back().map(|s| s.time) == newest,
+            };
+            for sample in process.energy_impact.since(from) {
+                let Some(&(dt, joules)) = in_window(sample.time) else {
+                    continue;
+                };
+                consumer.energy_impact += sample.value * dt;
+                let total = total_impact[&sample.time.to_bits()];
+                if total > 0.0 {
+                    consumer.joules += joules * sample.value / total;
+                }
+            }
+            consumer
+        })
+        .filter(|consumer| consumer.energy_impact > 0.0)
+        .collect();
+    consumers.sort_by(|a, b| {
+        b.joules.total_cmp(&a.joules).then(b.energy_impact.total_cmp(&a.energy_impact))
+    });
+    consumers
+}
+
+/// Formats seconds as `1h02m03s`, `2m03s` or `3s`.
+pub fn format_duration(seconds: f64) -> String {
+    let seconds = seconds.max(0.0).round() as u64;
+    let (hours, minutes, seconds) = (seconds / 3600, seconds / 60 % 60, seconds % 60);
+    if hours > 0 {
+        format!("{hours}h{minutes:02}m{seconds:02}s")
+    } else if minutes > 0 {
+        format!("{minutes}m{seconds:02}s")
+    } else {
+        format!("{seconds}s")
+    }
+}
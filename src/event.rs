@@ -1,8 +1,17 @@
-use std::{sync::mpsc, thread};
+use std::{
+    io::{self, Read},
+    sync::mpsc,
+    thread,
+};
 
 use crossterm::event::{self, Event as CrosstermEvent, KeyEvent, KeyEventKind, MouseEvent};
 
-use crate::{app::AppResult, metrics::Metrics};
+use crate::{
+    app::AppResult,
+    metrics::{Collector, Degraded, Metrics},
+    privilege,
+    remote::{self, ConnectionState},
+};
 
 /// Terminal events.
 #[derive(Clone, Debug)]
@@ -15,8 +24,22 @@ pub enum Event {
     Mouse(MouseEvent),
     /// Terminal resize.
     Resize(u16, u16),
-    /// Metrix
-    Metrics(Metrics),
+    /// Metrix of the host at this index.
+    Metrics(usize, Box<Metrics>),
+    /// Remote agent connection change of the host at this index.
+    Connection(usize, ConnectionState),
+}
+
+/// Where metrics come from.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Source {
+    /// Run `powermetrics` on this machine.
+    Local,
+    /// Run `powermetrics` on this machine in a helper started with `sudo`, falling back to
+    /// [`Source::Local`] if it stops.
+    Helper,
+    /// Receive snapshots from the agent at this address.
+    Remote(String),
 }
 
 /// Terminal event handler.
@@ -31,7 +54,10 @@ pub struct EventHandler {
 
 impl EventHandler {
     /// Constructs a new instance of [`EventHandler`].
-    pub fn new(_tick_rate: u64) -> Self {
+    ///
+    /// One collector or client thread is spawned per source, events are tagged with the index of
+    /// their source.
+    pub fn new(_tick_rate: u64, sources: Vec<Source>) -> Self {
         let (tx, rx) = mpsc::channel();
         let tx_key = tx.clone();
         thread::spawn(move || loop {
@@ -50,15 +76,24 @@ impl EventHandler {
                 CrosstermEvent::Paste(_) => unimplemented!(),
             }.expect("failed to send terminal event")
         });
-        let tx_metrics = tx.clone();
 
-        thread::spawn(move || loop {
-            let mut metrics = Metrics::default();
-            metrics.collect_metrics();
-            if let Err(msg) = tx_metrics.send(Event::Metrics(metrics)) {
-                panic!("{}", msg);
+        for (index, source) in sources.into_iter().enumerate() {
+            let tx_metrics = tx.clone();
+            match source {
+                Source::Local => {
+                    thread::spawn(move || collect(index, tx_metrics, None));
+                },
+                Source::Helper => {
+                    thread::spawn(move || {
+                        // The terminal is in use by now, the fallback shows why the helper stopped.
+                        let reason = receive_helper(index, &tx_metrics)
+                            .unwrap_or_else(|err| err.to_string());
+                        collect(index, tx_metrics, Some(Degraded::HelperFailed(reason)))
+                    });
+                },
+                Source::Remote(addr) => remote::spawn_client(index, addr, tx_metrics),
             }
-        });
+        }
         Self { sender: tx, receiver: rx }
     }
 
@@ -70,3 +105,44 @@ impl EventHandler {
         Ok(self.receiver.recv()?)
     }
 }
+
+/// Collects metrics on this machine forever. Snapshots taken without root say `not_root` instead,
+/// e.g. why the root helper stopped.
+fn collect(index: usize, sender: mpsc::Sender<Event>, not_root: Option<Degraded>) {
+    let mut collector = Collector::new();
+    loop {
+        let mut metrics = Box::new(collector.collect());
+        if metrics.degraded == Some(Degraded::NotRoot) && not_root.is_some() {
+            metrics.degraded = not_root.clone();
+        }
+        if let Err(msg) = sender.send(Event::Metrics(index, metrics)) {
+            panic!("{}", msg);
+        }
+    }
+}
+
+/// Forwards the snapshots of the root helper until it exits, and returns why it did: its exit
+/// status and last error line.
+fn receive_helper(index: usize, sender: &mpsc::Sender<Event>) -> AppResult<String> {
+    let mut helper = privilege::spawn_helper()?;
+    let stdout = helper.stdout.take().ok_or_else(|| io::Error::other("no helper output"))?;
+    let mut stderr = helper.stderr.take().ok_or_else(|| io::Error::other("no helper output"))?;
+    // Read meanwhile, so the helper never blocks on a full pipe.
+    let errors = thread::spawn(move || {
+        let mut errors = String::new();
+        let _ = stderr.read_to_string(&mut errors);
+        errors
+    });
+    let result = remote::receive(index, stdout, sender);
+    // Its stdout closed as it exits, unless it printed something else than snapshots.
+    if result.is_err() {
+        let _ = helper.kill();
+    }
+    let status = helper.wait()?;
+    let errors = errors.join().unwrap_or_default();
+    result?;
+    Ok(match errors.lines().map(str::trim).rfind(|line| !line.is_empty()) {
+        Some(error) => format!("{status}: {error}"),
+        None => status.to_string(),
+    })
+}
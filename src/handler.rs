@@ -1,28 +1,173 @@
-use crate::app::{App, AppResult};
-use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+use crate::{
+    app::{App, AppResult},
+    keymap::Action,
+    layout::{Panel, Tab},
+};
+use crossterm::event::{KeyCode, KeyEvent, KeyModifiers, MouseButton, MouseEvent, MouseEventKind};
+use std::time::Instant;
 
 /// Handles the key events and updates the state of [`App`].
 pub fn handle_key_events(key_event: KeyEvent, app: &mut App) -> AppResult<()> {
-    match key_event.code {
-        // Exit application on `ESC` or `q`
-        KeyCode::Esc | KeyCode::Char('q') => {
+    // Exit application on `Ctrl-C`, whatever the keymap says
+    if matches!(key_event.code, KeyCode::Char('c') | KeyCode::Char('C'))
+        && key_event.modifiers == KeyModifiers::CONTROL
+    {
+        app.quit();
+        return Ok(());
+    }
+    app.status = None;
+    // Keys go to the process filter while it's typed
+    if app.editing_filter {
+        let plain = !key_event.modifiers.intersects(KeyModifiers::CONTROL | KeyModifiers::ALT);
+        match key_event.code {
+            KeyCode::Enter => app.apply_filter(),
+            KeyCode::Esc => app.clear_filter(),
+            KeyCode::Backspace => app.pop_filter(),
+            KeyCode::Char(char) if plain => app.push_filter(char),
+            _ => {}
+        }
+        return Ok(());
+    }
+    if let Some(action) = app.keymap.action(key_event) {
+        perform(action, app);
+    }
+    Ok(())
+}
+
+/// Handles the mouse events and updates the state of [`App`].
+pub fn handle_mouse_events(mouse_event: MouseEvent, app: &mut App) -> AppResult<()> {
+    app.pointer = Some((mouse_event.column, mouse_event.row));
+    // Overlays cover the panels
+    if app.has_overlay() {
+        return Ok(());
+    }
+    let Some(panel) = app.panel_at(mouse_event.column, mouse_event.row) else {
+        return Ok(());
+    };
+    match mouse_event.kind {
+        MouseEventKind::Down(MouseButton::Left) => app.click(panel, Instant::now()),
+        // The wheel scrolls the process list, or moves through history anywhere else
+        MouseEventKind::ScrollDown if panel == Panel::Processes => app.scroll_processes(3),
+        MouseEventKind::ScrollUp if panel == Panel::Processes => app.scroll_processes(-3),
+        MouseEventKind::ScrollUp => app.scrub_back(1),
+        MouseEventKind::ScrollDown => app.scrub_forward(1),
+        _ => {}
+    }
+    Ok(())
+}
+
+/// Updates the state of [`App`] for `action`.
+fn perform(action: Action, app: &mut App) {
+    match action {
+        Action::Quit => {
+            app.quit();
+        }
+        // Close the open overlay, restore the maximized panel, go back to the dashboard, or quit
+        Action::Back if app.show_help => {
+            app.toggle_help();
+        }
+        Action::Back if app.show_stats => {
+            app.toggle_stats();
+        }
+        Action::Back if app.show_energy => {
+            app.toggle_energy();
+        }
+        Action::Back if app.show_alerts => {
+            app.toggle_alerts();
+        }
+        Action::Back if app.show_degraded => {
+            app.dismiss_degraded();
+        }
+        Action::Back if !app.process_filter.is_empty() => {
+            app.clear_filter();
+        }
+        Action::Back if app.maximized.is_some() => {
+            app.restore();
+        }
+        Action::Back if app.can_go_back() => {
+            app.back();
+        }
+        Action::Back => {
             app.quit();
         }
-        // Exit application on `Ctrl-C`
-        KeyCode::Char('c') | KeyCode::Char('C') => {
-            if key_event.modifiers == KeyModifiers::CONTROL {
-                app.quit();
+        Action::ToggleHelp => {
+            app.toggle_help();
+        }
+        // Tab handlers
+        Action::NextTab => {
+            app.next_tab();
+        }
+        Action::PreviousTab => {
+            app.previous_tab();
+        }
+        Action::TabOverview
+        | Action::TabCpu
+        | Action::TabGpu
+        | Action::TabPower
+        | Action::TabProcesses
+        | Action::TabNetDisk => {
+            if let Some(&tab) = Tab::ALL.iter().find(|tab| tab.action() == action) {
+                app.select_tab(tab);
             }
         }
-        // Counter handlers
-        KeyCode::Right => {
-            app.increment_counter();
+        // Timeline handlers
+        Action::TogglePause => {
+            app.toggle_pause();
         }
-        KeyCode::Left => {
-            app.decrement_counter();
+        Action::GoLive => {
+            app.go_live();
         }
-        // Other handlers you could add here.
-        _ => {}
+        Action::ScrubBack => {
+            app.scrub_back(1);
+        }
+        Action::ScrubForward => {
+            app.scrub_forward(1);
+        }
+        Action::ScrubBackFast => {
+            app.scrub_back(10);
+        }
+        Action::ScrubForwardFast => {
+            app.scrub_forward(10);
+        }
+        // Statistics handlers
+        Action::NextStatsWindow => {
+            app.next_stats_window();
+        }
+        Action::ZoomIn => {
+            app.zoom_in();
+        }
+        Action::ZoomOut => {
+            app.zoom_out();
+        }
+        Action::ToggleStats => {
+            app.toggle_stats();
+        }
+        Action::ToggleEnergy => {
+            app.toggle_energy();
+        }
+        Action::ToggleAlerts => {
+            app.toggle_alerts();
+        }
+        // Process list handlers
+        Action::SortProcesses => {
+            app.next_process_sort();
+        }
+        Action::FilterProcesses => {
+            app.edit_filter();
+        }
+        Action::Export => {
+            app.export();
+        }
+        // Dashboard handlers
+        Action::SelectNext if app.is_dashboard() => {
+            app.select_next();
+        }
+        Action::SelectPrevious if app.is_dashboard() => {
+            app.select_previous();
+        }
+        Action::DrillDown if app.is_dashboard() => {
+            app.drill_down();
+        }
+        Action::SelectNext | Action::SelectPrevious | Action::DrillDown => {}
     }
-    Ok(())
 }
@@ -0,0 +1,386 @@
+use std::collections::{HashMap, VecDeque};
+
+use crate::metrics::Metrics;
+
+/// Fixed-capacity buffer that drops its oldest element when full.
+#[derive(Debug, Clone)]
+pub struct RingBuffer<T> {
+    items: VecDeque<T>,
+    capacity: usize,
+}
+
+impl<T> RingBuffer<T> {
+    /// Constructs an empty buffer holding at most `capacity` elements.
+    pub fn new(capacity: usize) -> Self {
+        Self { items: VecDeque::new(), capacity: capacity.max(1) }
+    }
+
+    /// Appends `item`, dropping the oldest element if the buffer is full.
+    pub fn push(&mut self, item: T) {
+        if self.items.len() == self.capacity {
+            self.items.pop_front();
+        }
+        self.items.push_back(item);
+    }
+
+    pub fn len(&self) -> usize {
+        self.items.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.items.is_empty()
+    }
+
+    pub fn capacity(&self) -> usize {
+        self.capacity
+    }
+
+    /// Oldest element.
+    pub fn first(&self) -> Option<&T> {
+        self.items.front()
+    }
+
+    /// Newest element.
+    pub fn last(&self) -> Option<&T> {
+        self.items.back()
+    }
+
+    /// Element at `index`, counted from the oldest.
+    pub fn get(&self, index: usize) -> Option<&T> {
+        self.items.get(index)
+    }
+
+    /// Iterates from the oldest to the newest element.
+    pub fn iter(&self) -> impl DoubleEndedIterator<Item = &T> + ExactSizeIterator {
+        self.items.iter()
+    }
+}
+
+/// A timestamped value.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Sample {
+    /// Seconds since the Unix epoch.
+    pub time: f64,
+    pub value: f64,
+}
+
+/// The recorded values of one metric.
+pub type Series = RingBuffer<Sample>;
+
+impl Series {
+    /// Latest value.
+    pub fn latest(&self) -> Option<f64> {
+        self.last().map(|sample| sample.value)
+    }
+
+    /// Samples taken at or after `time`.
+    pub fn since(&self, time: f64) -> impl DoubleEndedIterator<Item = &Sample> {
+        self.iter().filter(move |sample| sample.time >= time)
+    }
+
+    /// Samples taken at or before `time`.
+    pub fn until(&self, time: f64) -> impl DoubleEndedIterator<Item = &Sample> {
+        self.iter().filter(move |sample| sample.time <= time)
+    }
+
+    /// The newest `count` samples taken at or before `time`.
+    pub fn tail(&self, count: usize, time: f64) -> impl Iterator<Item = &Sample> {
+        let mut tail: Vec<&Sample> = self.until(time).rev().take(count).collect();
+        tail.reverse();
+        tail.into_iter()
+    }
+
+    /// Value of the newest sample taken at or before `time`.
+    pub fn value_at(&self, time: f64) -> Option<f64> {
+        self.until(time).next_back().map(|sample| sample.value)
+    }
+
+    /// Integral of the values between `from` and `to` with the trapezoidal rule, e.g. joules for
+    /// a series in watts.
+    pub fn integrate(&self, from: f64, to: f64) -> f64 {
+        let samples: Vec<&Sample> =
+            self.iter().filter(|sample| sample.time >= from && sample.time <= to).collect();
+        samples
+            .windows(2)
+            .map(|pair| (pair[0].value + pair[1].value) / 2.0 * (pair[1].time - pair[0].time))
+            .sum()
+    }
+}
+
+/// Every metric that is recorded in [`History`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum MetricKey {
+    ECpuActive,
+    ECpuFreq,
+    PCpuActive,
+    PCpuFreq,
+    GpuActive,
+    GpuFreq,
+    CpuPower,
+    GpuPower,
+    AnePower,
+    PackagePower,
+    MemoryUsed,
+    SwapUsed,
+    NetInBytes,
+    NetOutBytes,
+    NetInPackets,
+    NetOutPackets,
+    DiskReadOps,
+    DiskWriteOps,
+    DiskReadKBytes,
+    DiskWriteKBytes,
+}
+
+impl MetricKey {
+    pub const ALL: [MetricKey; 20] = [
+        MetricKey::ECpuActive,
+        MetricKey::ECpuFreq,
+        MetricKey::PCpuActive,
+        MetricKey::PCpuFreq,
+        MetricKey::GpuActive,
+        MetricKey::GpuFreq,
+        MetricKey::CpuPower,
+        MetricKey::GpuPower,
+        MetricKey::AnePower,
+        MetricKey::PackagePower,
+        MetricKey::MemoryUsed,
+        MetricKey::SwapUsed,
+        MetricKey::NetInBytes,
+        MetricKey::NetOutBytes,
+        MetricKey::NetInPackets,
+        MetricKey::NetOutPackets,
+        MetricKey::DiskReadOps,
+        MetricKey::DiskWriteOps,
+        MetricKey::DiskReadKBytes,
+        MetricKey::DiskWriteKBytes,
+    ];
+
+    /// Name in alert rules, e.g. `package_w`.
+    pub fn name(self) -> &'static str {
+        match self {
+            MetricKey::ECpuActive => "e_cpu",
+            MetricKey::ECpuFreq => "e_cpu_freq",
+            MetricKey::PCpuActive => "p_cpu",
+            MetricKey::PCpuFreq => "p_cpu_freq",
+            MetricKey::GpuActive => "gpu",
+            MetricKey::GpuFreq => "gpu_freq",
+            MetricKey::CpuPower => "cpu_w",
+            MetricKey::GpuPower => "gpu_w",
+            MetricKey::AnePower => "ane_w",
+            MetricKey::PackagePower => "package_w",
+            MetricKey::MemoryUsed => "memory_used",
+            MetricKey::SwapUsed => "swap_used",
+            MetricKey::NetInBytes => "net_in",
+            MetricKey::NetOutBytes => "net_out",
+            MetricKey::NetInPackets => "net_in_packets",
+            MetricKey::NetOutPackets => "net_out_packets",
+            MetricKey::DiskReadOps => "disk_read_ops",
+            MetricKey::DiskWriteOps => "disk_write_ops",
+            MetricKey::DiskReadKBytes => "disk_read",
+            MetricKey::DiskWriteKBytes => "disk_write",
+        }
+    }
+
+    /// Human readable name.
+    pub fn label(self) -> &'static str {
+        match self {
+            MetricKey::ECpuActive => "E-CPU usage",
+            MetricKey::ECpuFreq => "E-CPU frequency",
+            MetricKey::PCpuActive => "P-CPU usage",
+            MetricKey::PCpuFreq => "P-CPU frequency",
+            MetricKey::GpuActive => "GPU usage",
+            MetricKey::GpuFreq => "GPU frequency",
+            MetricKey::CpuPower => "CPU power",
+            MetricKey::GpuPower => "GPU power",
+            MetricKey::AnePower => "ANE power",
+            MetricKey::PackagePower => "Package power",
+            MetricKey::MemoryUsed => "Memory used",
+            MetricKey::SwapUsed => "Swap used",
+            MetricKey::NetInBytes => "Network in",
+            MetricKey::NetOutBytes => "Network out",
+            MetricKey::NetInPackets => "Network in packets",
+            MetricKey::NetOutPackets => "Network out packets",
+            MetricKey::DiskReadOps => "Disk read ops",
+            MetricKey::DiskWriteOps => "Disk write ops",
+            MetricKey::DiskReadKBytes => "Disk read",
+            MetricKey::DiskWriteKBytes => "Disk write",
+        }
+    }
+
+    /// Unit of the recorded values.
+    pub fn unit(self) -> &'static str {
+        match self {
+            MetricKey::ECpuActive | MetricKey::PCpuActive | MetricKey::GpuActive => "%",
+            MetricKey::ECpuFreq | MetricKey::PCpuFreq | MetricKey::GpuFreq => "MHz",
+            MetricKey::CpuPower
+            | MetricKey::GpuPower
+            | MetricKey::AnePower
+            | MetricKey::PackagePower => "W",
+            MetricKey::MemoryUsed | MetricKey::SwapUsed => "GB",
+            MetricKey::NetInBytes | MetricKey::NetOutBytes => "bytes/s",
+            MetricKey::NetInPackets | MetricKey::NetOutPackets => "packets/s",
+            MetricKey::DiskReadOps | MetricKey::DiskWriteOps => "ops/s",
+            MetricKey::DiskReadKBytes | MetricKey::DiskWriteKBytes => "KBytes/s",
+        }
+    }
+
+    /// Is this metric only read from `powermetrics`, and so missing from degraded snapshots?
+    pub fn needs_powermetrics(self) -> bool {
+        !matches!(
+            self,
+            MetricKey::ECpuActive
+                | MetricKey::PCpuActive
+                | MetricKey::MemoryUsed
+                | MetricKey::SwapUsed
+                | MetricKey::NetInBytes
+                | MetricKey::NetOutBytes
+                | MetricKey::NetInPackets
+                | MetricKey::NetOutPackets
+        )
+    }
+
+    /// Reads this metric from a snapshot.
+    pub fn value(self, metrics: &Metrics) -> f64 {
+        let cpu = &metrics.cpu_metrics;
+        let net_disk = &metrics.net_disk_metrics;
+        match self {
+            MetricKey::ECpuActive => cpu.e_cluster_active as f64,
+            MetricKey::ECpuFreq => cpu.e_cluster_freq_mhz as f64,
+            MetricKey::PCpuActive => cpu.p_cluster_active as f64,
+            MetricKey::PCpuFreq => cpu.p_cluster_freq_mhz as f64,
+            MetricKey::GpuActive => metrics.gpu_metrics.active,
+            MetricKey::GpuFreq => metrics.gpu_metrics.freq_mhz as f64,
+            MetricKey::CpuPower => cpu.cpu_w,
+            MetricKey::GpuPower => cpu.gpu_w,
+            MetricKey::AnePower => cpu.ane_w,
+            MetricKey::PackagePower => cpu.package_w,
+            MetricKey::MemoryUsed => metrics.mem.used as f64 / 1024.0 / 1024.0 / 1024.0,
+            MetricKey::SwapUsed => metrics.mem.swap_used as f64 / 1024.0 / 1024.0 / 1024.0,
+            MetricKey::NetInBytes => net_disk.in_bytes_per_sec,
+            MetricKey::NetOutBytes => net_disk.out_bytes_per_sec,
+            MetricKey::NetInPackets => net_disk.in_packets_per_sec,
+            MetricKey::NetOutPackets => net_disk.out_packets_per_sec,
+            MetricKey::DiskReadOps => net_disk.read_ops_per_sec,
+            MetricKey::DiskWriteOps => net_disk.write_ops_per_sec,
+            MetricKey::DiskReadKBytes => net_disk.read_k_bytes_per_sec,
+            MetricKey::DiskWriteKBytes => net_disk.write_k_bytes_per_sec,
+        }
+    }
+}
+
+/// The recorded values of one process name, summed over all its pids.
+#[derive(Debug, Clone)]
+pub struct ProcessHistory {
+    /// CPU usage in percent of one core.
+    pub cpu: Series,
+    pub energy_impact: Series,
+}
+
+impl ProcessHistory {
+    fn new(retention: usize) -> Self {
+        Self { cpu: Series::new(retention), energy_impact: Series::new(retention) }
+    }
+}
+
+/// Processes kept per snapshot, by energy impact.
+const SNAPSHOT_PROCESSES: usize = 64;
+
+/// Timestamped series of every metric, each keeping the newest `retention` samples.
+#[derive(Debug, Clone)]
+pub struct History {
+    retention: usize,
+    series: HashMap<MetricKey, Series>,
+    processes: HashMap<String, ProcessHistory>,
+    /// Whole snapshots, to show the state at any point of the timeline.
+    snapshots: RingBuffer<Metrics>,
+}
+
+impl Default for History {
+    fn default() -> Self {
+        Self::new(3600)
+    }
+}
+
+impl History {
+    /// Constructs an empty history keeping `retention` samples per series.
+    pub fn new(retention: usize) -> Self {
+        let series = MetricKey::ALL.iter().map(|&key| (key, Series::new(retention))).collect();
+        Self { retention, series, processes: HashMap::new(), snapshots: RingBuffer::new(retention) }
+    }
+
+    /// Number of samples kept per series.
+    pub fn retention(&self) -> usize {
+        self.retention
+    }
+
+    /// Has nothing been recorded yet?
+    pub fn is_empty(&self) -> bool {
+        self.snapshots.is_empty()
+    }
+
+    /// Number of recorded snapshots.
+    pub fn len(&self) -> usize {
+        self.snapshots.len()
+    }
+
+    /// The snapshot recorded `back` samples before the newest one.
+    pub fn snapshot(&self, back: usize) -> Option<&Metrics> {
+        self.snapshots.len().checked_sub(back + 1).and_then(|index| self.snapshots.get(index))
+    }
+
+    /// Appends every metric of `metrics`.
+    pub fn record(&mut self, metrics: &Metrics) {
+        let time = metrics.timestamp;
+        for (key, series) in self.series.iter_mut() {
+            series.push(Sample { time, value: key.value(metrics) });
+        }
+
+        let mut totals: HashMap<&str, (f64, f64)> = HashMap::new();
+        for process in &metrics.processes {
+            let total = totals.entry(process.name.as_str()).or_default();
+            total.0 += process.cpu_percent();
+            total.1 += process.energy_impact;
+        }
+        for (name, (cpu, energy_impact)) in totals {
+            let retention = self.retention;
+            let process = self
+                .processes
+                .entry(name.to_string())
+                .or_insert_with(|| ProcessHistory::new(retention));
+            process.cpu.push(Sample { time, value: cpu });
+            process.energy_impact.push(Sample { time, value: energy_impact });
+        }
+
+        let mut snapshot = metrics.clone();
+        snapshot.processes.sort_by(|a, b| b.energy_impact.total_cmp(&a.energy_impact));
+        snapshot.processes.truncate(SNAPSHOT_PROCESSES);
+        self.snapshots.push(snapshot);
+
+        // Processes that exited are kept until their samples leave the retention window.
+        if let Some(oldest) = self.series(MetricKey::PackagePower).first().map(|s| s.time) {
+            self.processes
+                .retain(|_, process| process.cpu.last().is_some_and(|s| s.time >= oldest));
+        }
+    }
+
+    /// The series of `key`.
+    pub fn series(&self, key: MetricKey) -> &Series {
+        &self.series[&key]
+    }
+
+    /// Latest value of `key`.
+    pub fn latest(&self, key: MetricKey) -> Option<f64> {
+        self.series(key).latest()
+    }
+
+    /// Value of `key` at `time`.
+    pub fn value_at(&self, key: MetricKey, time: f64) -> Option<f64> {
+        self.series(key).value_at(time)
+    }
+
+    /// Series of every process seen within the retention window, by name.
+    pub fn processes(&self) -> &HashMap<String, ProcessHistory> {
+        &self.processes
+    }
+}
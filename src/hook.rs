@@ -0,0 +1,255 @@
+use std::{
+    collections::HashMap,
+    fmt,
+    io::{BufRead, BufReader, Write},
+    net::{TcpStream, ToSocketAddrs},
+    process::{Command, Stdio},
+    thread,
+    time::{Duration, Instant},
+};
+
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    alert::{Alert, AlertRule, Change},
+    app::AppResult,
+};
+
+/// `[alerts.hooks]` section of the config, run for every alert.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct HooksConfig {
+    /// Run with `sh -c`, the alert being in `MACTOP_ALERT_*` environment variables.
+    pub command: Option<String>,
+    /// Receives the alert as JSON in a POST request.
+    pub webhook: Option<Webhook>,
+    /// Seconds a command or request may take before it is killed or abandoned.
+    pub timeout: f64,
+    /// Seconds between two runs for the same rule, host and change, more are dropped.
+    pub min_interval: f64,
+}
+
+impl Default for HooksConfig {
+    fn default() -> Self {
+        Self { command: None, webhook: None, timeout: 10.0, min_interval: 60.0 }
+    }
+}
+
+/// A plain `http://` URL receiving alerts.
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+#[serde(try_from = "String")]
+pub struct Webhook {
+    pub host: String,
+    pub port: u16,
+    /// Path and query, starting with `/`.
+    pub path: String,
+}
+
+impl TryFrom<String> for Webhook {
+    type Error = String;
+
+    fn try_from(url: String) -> Result<Self, Self::Error> {
+        let Some(rest) = url.strip_prefix("http://") else {
+            return Err(format!("invalid webhook `{url}`, only http:// URLs are supported"));
+        };
+        let (authority, path) = match rest.find('/') {
+            Some(index) => rest.split_at(index),
+            None => (rest, "/"),
+        };
+        let (host, port) = match authority.rsplit_once(':') {
+            Some((host, port)) => {
+                let port = port.parse().map_err(|_| format!("invalid port in webhook `{url}`"))?;
+                (host, port)
+            },
+            None => (authority, 80),
+        };
+        if host.is_empty() {
+            return Err(format!("invalid webhook `{url}`, the host is missing"));
+        }
+        Ok(Self { host: host.to_string(), port, path: path.to_string() })
+    }
+}
+
+impl fmt::Display for Webhook {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "http://{}:{}{}", self.host, self.port, self.path)
+    }
+}
+
+/// An alert that fired or resolved, as sent to hooks.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Notification {
+    pub host: String,
+    /// `fired` or `resolved`.
+    pub state: String,
+    pub title: String,
+    /// Condition of the rule, e.g. `package_w > 25 for 10s`.
+    pub rule: String,
+    pub severity: String,
+    pub value: String,
+    /// Seconds since the Unix epoch.
+    pub time: f64,
+}
+
+impl Notification {
+    pub fn new(host: &str, change: Change, alert: &Alert, rule: &AlertRule, time: f64) -> Self {
+        Self {
+            host: host.to_string(),
+            state: change.label().to_string(),
+            title: alert.title.clone(),
+            rule: rule.when.to_string(),
+            severity: alert.severity.label().to_string(),
+            value: alert.value.clone(),
+            time,
+        }
+    }
+
+    /// Environment variables given to commands.
+    pub fn env(&self) -> [(&'static str, String); 7] {
+        [
+            ("MACTOP_ALERT_HOST", self.host.clone()),
+            ("MACTOP_ALERT_STATE", self.state.clone()),
+            ("MACTOP_ALERT_TITLE", self.title.clone()),
+            ("MACTOP_ALERT_RULE", self.rule.clone()),
+            ("MACTOP_ALERT_SEVERITY", self.severity.clone()),
+            ("MACTOP_ALERT_VALUE", self.value.clone()),
+            ("MACTOP_ALERT_TIME", format!("{:.0}", self.time)),
+        ]
+    }
+}
+
+/// Runs `command` with `sh -c`, killing it after `timeout`.
+pub fn run_command(command: &str, notification: &Notification, timeout: Duration) -> AppResult<()> {
+    let mut child = Command::new("sh")
+        .args(["-c", command])
+        .envs(notification.env())
+        .stdin(Stdio::null())
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .spawn()?;
+    let start = Instant::now();
+    loop {
+        if let Some(status) = child.try_wait()? {
+            if !status.success() {
+                return Err(format!("hook `{command}` failed: {status}").into());
+            }
+            return Ok(());
+        }
+        if start.elapsed() >= timeout {
+            child.kill()?;
+            child.wait()?;
+            return Err(format!("hook `{command}` timed out").into());
+        }
+        thread::sleep(Duration::from_millis(20));
+    }
+}
+
+/// POSTs `notification` as JSON to `webhook`, failing unless it answers with a 2xx status within
+/// `timeout` per connection, write and read.
+pub fn post_webhook(
+    webhook: &Webhook,
+    notification: &Notification,
+    timeout: Duration,
+) -> AppResult<()> {
+    let body = serde_json::to_string(notification)?;
+    let addrs = (webhook.host.as_str(), webhook.port).to_socket_addrs()?;
+    let mut stream = None;
+    for addr in addrs {
+        if let Ok(connected) = TcpStream::connect_timeout(&addr, timeout) {
+            stream = Some(connected);
+            break;
+        }
+    }
+    let mut stream = stream.ok_or_else(|| format!("failed to connect to webhook {webhook}"))?;
+    stream.set_read_timeout(Some(timeout))?;
+    stream.set_write_timeout(Some(timeout))?;
+    write!(
+        stream,
+        "POST {} HTTP/1.1\r\nHost: {}:{}\r\nUser-Agent: mactop-rs\r\n\
+         Content-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{body}",
+        webhook.path,
+        webhook.host,
+        webhook.port,
+        body.len()
+    )?;
+    stream.flush()?;
+
+    // Only the status line matters, e.g. `HTTP/1.1 204 No Content`.
+    let mut status_line = String::new();
+    BufReader::new(stream).read_line(&mut status_line)?;
+    let status = status_line.split_whitespace().nth(1).and_then(|code| code.parse::<u16>().ok());
+    match status {
+        Some(200..=299) => Ok(()),
+        Some(status) => Err(format!("webhook {webhook} answered {status}").into()),
+        None => Err(format!("invalid answer from webhook {webhook}").into()),
+    }
+}
+
+/// Drops hooks run again too soon for the same key.
+#[derive(Debug, Clone, Default)]
+pub struct RateLimit {
+    /// Seconds between two runs.
+    min_interval: f64,
+    /// Time of the last run allowed per key.
+    last: HashMap<String, f64>,
+}
+
+impl RateLimit {
+    pub fn new(min_interval: f64) -> Self {
+        Self { min_interval, last: HashMap::new() }
+    }
+
+    /// Can a hook for `key` run at `time`? Records the run if so.
+    pub fn allow(&mut self, key: &str, time: f64) -> bool {
+        match self.last.get(key) {
+            Some(&last) if time - last < self.min_interval => false,
+            _ => {
+                self.last.insert(key.to_string(), time);
+                true
+            },
+        }
+    }
+}
+
+/// Runs the commands and webhooks of alerts in the background.
+#[derive(Debug, Clone, Default)]
+pub struct Hooks {
+    config: HooksConfig,
+    limit: RateLimit,
+}
+
+impl Hooks {
+    pub fn new(config: &HooksConfig) -> Self {
+        Self { config: config.clone(), limit: RateLimit::new(config.min_interval) }
+    }
+
+    /// Starts the hooks of every alert and those of `rule`, the rule at `index` in the config, for
+    /// `notification`, unless rate limited.
+    ///
+    /// Hooks run on their own thread so a slow command never blocks the interface, their failures
+    /// are ignored.
+    pub fn notify(&mut self, notification: Notification, index: usize, rule: &AlertRule) {
+        let commands: Vec<String> =
+            self.config.command.iter().chain(&rule.command).cloned().collect();
+        let webhooks: Vec<Webhook> =
+            self.config.webhook.iter().chain(&rule.webhook).cloned().collect();
+        if commands.is_empty() && webhooks.is_empty() {
+            return;
+        }
+        // Rules with the same condition but different hooks are limited apart.
+        let key = format!("{}\n{index}\n{}", notification.host, notification.state);
+        if !self.limit.allow(&key, notification.time) {
+            return;
+        }
+        // Checked when the config is loaded.
+        let timeout = Duration::try_from_secs_f64(self.config.timeout).unwrap_or_default();
+        thread::spawn(move || {
+            for command in &commands {
+                let _ = run_command(command, &notification, timeout);
+            }
+            for webhook in &webhooks {
+                let _ = post_webhook(webhook, &notification, timeout);
+            }
+        });
+    }
+}
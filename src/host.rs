@@ -0,0 +1,131 @@
+use std::{borrow::Cow, cell::RefCell};
+
+use crate::{
+    alert::{Alert, Alerts, Change},
+    app::AppResult,
+    config::StoreConfig,
+    energy::EnergyMeter,
+    history::History,
+    metrics::{now, Metrics},
+    remote::ConnectionState,
+    store::Store,
+};
+
+/// State of one monitored machine.
+#[derive(Debug, Default)]
+pub struct Host {
+    /// Address of the agent, `None` for this machine.
+    pub addr: Option<String>,
+
+    /// State of the connection to [`Host::addr`].
+    pub connection: ConnectionState,
+
+    /// metrics
+    pub metrics: Metrics,
+
+    /// history
+    pub history: History,
+
+    /// Energy used since mactop-rs started.
+    pub energy: EnergyMeter,
+
+    /// Alert rules evaluated on every snapshot.
+    pub alerts: Alerts,
+
+    /// Where snapshots are saved, `None` if disabled or after a write error.
+    pub store: Option<Store>,
+
+    /// Why snapshots stopped being saved, shown until the end of the session.
+    pub store_error: Option<String>,
+
+    /// Last result of [`Host::alerts_at`] and its `back`, until the next snapshot.
+    pub replayed_alerts: RefCell<Option<(usize, Alerts)>>,
+}
+
+impl Host {
+    /// Constructs the [`Host`] for this machine, keeping `retention` samples of history.
+    pub fn local(retention: usize) -> Self {
+        Self {
+            connection: ConnectionState::Connected,
+            history: History::new(retention),
+            ..Default::default()
+        }
+    }
+
+    /// Constructs a [`Host`] for the agent at `addr`, keeping `retention` samples of history.
+    pub fn remote(addr: String, retention: usize) -> Self {
+        Self { addr: Some(addr), history: History::new(retention), ..Default::default() }
+    }
+
+    /// Name shown for this host.
+    pub fn name(&self) -> &str {
+        self.addr.as_deref().unwrap_or("localhost")
+    }
+
+    /// Has at least one snapshot been received since startup? Reloaded history doesn't count.
+    pub fn has_metrics(&self) -> bool {
+        self.metrics.timestamp > 0.0
+    }
+
+    /// Opens the on-disk store of this host and reloads its recent history into
+    /// [`Host::history`].
+    ///
+    /// On error the host runs without a store, like after a write error.
+    pub fn open_store(&mut self, config: &StoreConfig) -> AppResult<()> {
+        let result = self.try_open_store(config);
+        if let Err(err) = &result {
+            self.store_error = Some(err.to_string());
+        }
+        result
+    }
+
+    fn try_open_store(&mut self, config: &StoreConfig) -> AppResult<()> {
+        let store = Store::open(config, self.name())?;
+        store.prune()?;
+        let since = now() - config.reload_minutes * 60.0;
+        for metrics in store.load_since(since)? {
+            self.history.record(&metrics);
+        }
+        self.store = Some(store);
+        Ok(())
+    }
+
+    /// Alerts active `back` samples before the newest one, replayed over the history.
+    ///
+    /// Alerts that fired before the oldest snapshot kept are missed until they fire again. The
+    /// replay is cached, so a paused cursor only costs it once per snapshot.
+    pub fn alerts_at(&self, back: usize) -> Cow<'_, Alerts> {
+        if back == 0 {
+            return Cow::Borrowed(&self.alerts);
+        }
+        let mut cache = self.replayed_alerts.borrow_mut();
+        if let Some((_, alerts)) = cache.as_ref().filter(|(cached, _)| *cached == back) {
+            return Cow::Owned(alerts.clone());
+        }
+        let mut alerts = self.alerts.cleared();
+        for back in (back..self.history.len()).rev() {
+            if let Some(metrics) = self.history.snapshot(back) {
+                alerts.update(metrics);
+            }
+        }
+        *cache = Some((back, alerts.clone()));
+        Cow::Owned(alerts)
+    }
+
+    /// Stores a new snapshot, returning the alerts it fired or resolved.
+    pub fn on_metrics(&mut self, metrics: Metrics) -> Vec<(Change, Alert)> {
+        self.replayed_alerts.take();
+        self.history.record(&metrics);
+        self.energy.record(&metrics);
+        let changes = self.alerts.update(&metrics);
+        if let Some(store) = &mut self.store {
+            // A full disk must not take the monitor down, stop saving and say so instead.
+            if let Err(err) = store.append(&metrics) {
+                self.store = None;
+                self.store_error = Some(err.to_string());
+            }
+        }
+        self.metrics = metrics;
+        changes
+    }
+}
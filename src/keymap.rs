@@ -0,0 +1,342 @@
+use std::{collections::HashMap, fmt, str::FromStr};
+
+use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+use serde::Deserialize;
+
+/// Something a key can do.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum Action {
+    Quit,
+    /// Closes the overlay, goes back to the host list, or quits.
+    Back,
+    ToggleHelp,
+    NextTab,
+    PreviousTab,
+    TabOverview,
+    TabCpu,
+    TabGpu,
+    TabPower,
+    TabProcesses,
+    TabNetDisk,
+    TogglePause,
+    GoLive,
+    ScrubBack,
+    ScrubForward,
+    ScrubBackFast,
+    ScrubForwardFast,
+    NextStatsWindow,
+    ZoomIn,
+    ZoomOut,
+    ToggleStats,
+    ToggleEnergy,
+    ToggleAlerts,
+    SortProcesses,
+    /// Starts typing a filter on process names.
+    FilterProcesses,
+    /// Saves the snapshot shown to a JSON file.
+    Export,
+    SelectNext,
+    SelectPrevious,
+    DrillDown,
+}
+
+impl Action {
+    /// Every action, in the order of the help overlay.
+    pub const ALL: [Action; 29] = [
+        Action::Quit,
+        Action::Back,
+        Action::ToggleHelp,
+        Action::NextTab,
+        Action::PreviousTab,
+        Action::TabOverview,
+        Action::TabCpu,
+        Action::TabGpu,
+        Action::TabPower,
+        Action::TabProcesses,
+        Action::TabNetDisk,
+        Action::TogglePause,
+        Action::GoLive,
+        Action::ScrubBack,
+        Action::ScrubForward,
+        Action::ScrubBackFast,
+        Action::ScrubForwardFast,
+        Action::NextStatsWindow,
+        Action::ZoomIn,
+        Action::ZoomOut,
+        Action::ToggleStats,
+        Action::ToggleEnergy,
+        Action::ToggleAlerts,
+        Action::SortProcesses,
+        Action::FilterProcesses,
+        Action::Export,
+        Action::SelectNext,
+        Action::SelectPrevious,
+        Action::DrillDown,
+    ];
+
+    pub fn description(self) -> &'static str {
+        match self {
+            Action::Quit => "Quit",
+            Action::Back => "Close overlay / back to all hosts / quit",
+            Action::ToggleHelp => "Show or hide this help",
+            Action::NextTab => "Next tab",
+            Action::PreviousTab => "Previous tab",
+            Action::TabOverview => "Overview tab",
+            Action::TabCpu => "CPU tab",
+            Action::TabGpu => "GPU tab",
+            Action::TabPower => "Power tab",
+            Action::TabProcesses => "Processes tab",
+            Action::TabNetDisk => "Net/Disk tab",
+            Action::TogglePause => "Pause, or go back to live",
+            Action::GoLive => "Go back to live",
+            Action::ScrubBack => "One sample back in time",
+            Action::ScrubForward => "One sample forward in time",
+            Action::ScrubBackFast => "10 samples back in time",
+            Action::ScrubForwardFast => "10 samples forward in time",
+            Action::NextStatsWindow => "Change the statistics window",
+            Action::ZoomIn => "Show a shorter time span in charts",
+            Action::ZoomOut => "Show a longer time span in charts",
+            Action::ToggleStats => "Show or hide the statistics table",
+            Action::ToggleEnergy => "Show or hide the energy table",
+            Action::ToggleAlerts => "Show or hide the alert list",
+            Action::SortProcesses => "Sort processes by the next column",
+            Action::FilterProcesses => "Filter processes by name",
+            Action::Export => "Save the snapshot shown to a JSON file",
+            Action::SelectNext => "Select the next host",
+            Action::SelectPrevious => "Select the previous host",
+            Action::DrillDown => "Show the selected host",
+        }
+    }
+
+    /// Vim-style keys bound when the config does not say otherwise.
+    fn default_keys(self) -> &'static [&'static str] {
+        match self {
+            Action::Quit => &["q"],
+            Action::Back => &["esc"],
+            Action::ToggleHelp => &["?"],
+            Action::NextTab => &["tab"],
+            Action::PreviousTab => &["backtab"],
+            Action::TabOverview => &["1"],
+            Action::TabCpu => &["2"],
+            Action::TabGpu => &["3"],
+            Action::TabPower => &["4"],
+            Action::TabProcesses => &["5"],
+            Action::TabNetDisk => &["6"],
+            Action::TogglePause => &["space"],
+            Action::GoLive => &["end", "G"],
+            Action::ScrubBack => &["left", "h"],
+            Action::ScrubForward => &["right", "l"],
+            Action::ScrubBackFast => &["shift-left", "H"],
+            Action::ScrubForwardFast => &["shift-right", "L"],
+            Action::NextStatsWindow => &["w"],
+            Action::ZoomIn => &["+", "="],
+            Action::ZoomOut => &["-"],
+            Action::ToggleStats => &["s"],
+            Action::ToggleEnergy => &["e"],
+            Action::ToggleAlerts => &["a"],
+            Action::SortProcesses => &["o"],
+            Action::FilterProcesses => &["/"],
+            Action::Export => &["x"],
+            Action::SelectNext => &["down", "j"],
+            Action::SelectPrevious => &["up", "k"],
+            Action::DrillDown => &["enter"],
+        }
+    }
+}
+
+impl fmt::Display for Action {
+    /// Writes the name used in the config, e.g. `toggle-pause`.
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        for (index, char) in format!("{self:?}").chars().enumerate() {
+            if index > 0 && char.is_ascii_uppercase() {
+                f.write_str("-")?;
+            }
+            write!(f, "{}", char.to_ascii_lowercase())?;
+        }
+        Ok(())
+    }
+}
+
+/// A key with its modifiers, written `q`, `G`, `ctrl-d`, `shift-left`, `f1`, `space`...
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Deserialize)]
+#[serde(try_from = "String")]
+pub struct KeyBinding {
+    pub code: KeyCode,
+    pub modifiers: KeyModifiers,
+}
+
+impl KeyBinding {
+    /// Does `event` press this key?
+    pub fn matches(&self, event: KeyEvent) -> bool {
+        // Terminals disagree on reporting Shift with characters and back tab, the key says it all.
+        let modifiers = match event.code {
+            KeyCode::Char(_) | KeyCode::BackTab => event.modifiers - KeyModifiers::SHIFT,
+            _ => event.modifiers,
+        };
+        self.code == event.code && self.modifiers == modifiers
+    }
+}
+
+impl FromStr for KeyBinding {
+    type Err = String;
+
+    fn from_str(text: &str) -> Result<Self, Self::Err> {
+        let mut modifiers = KeyModifiers::NONE;
+        let mut rest = text;
+        // A lone `-` is a key, not a separator.
+        while let Some((modifier, key)) = rest.split_once('-').filter(|(_, key)| !key.is_empty()) {
+            modifiers |= match modifier.to_ascii_lowercase().as_str() {
+                "ctrl" => KeyModifiers::CONTROL,
+                "alt" => KeyModifiers::ALT,
+                "shift" => KeyModifiers::SHIFT,
+                _ => return Err(format!("unknown modifier `{modifier}` in key `{text}`")),
+            };
+            rest = key;
+        }
+        let code = match rest.to_ascii_lowercase().as_str() {
+            "esc" => KeyCode::Esc,
+            "enter" => KeyCode::Enter,
+            "space" => KeyCode::Char(' '),
+            "tab" => KeyCode::Tab,
+            "backtab" => KeyCode::BackTab,
+            "backspace" => KeyCode::Backspace,
+            "delete" => KeyCode::Delete,
+            "insert" => KeyCode::Insert,
+            "left" => KeyCode::Left,
+            "right" => KeyCode::Right,
+            "up" => KeyCode::Up,
+            "down" => KeyCode::Down,
+            "home" => KeyCode::Home,
+            "end" => KeyCode::End,
+            "pageup" => KeyCode::PageUp,
+            "pagedown" => KeyCode::PageDown,
+            name => match (rest.chars().next(), rest.chars().count()) {
+                (Some(char), 1) => KeyCode::Char(char),
+                _ => match name.strip_prefix('f').and_then(|number| number.parse().ok()) {
+                    Some(number @ 1..=12) => KeyCode::F(number),
+                    _ => return Err(format!("unknown key `{text}`")),
+                },
+            },
+        };
+        // `shift-g` is what terminals report as `G`.
+        if let KeyCode::Char(char) = code {
+            if modifiers.contains(KeyModifiers::SHIFT) {
+                let code = KeyCode::Char(char.to_ascii_uppercase());
+                return Ok(Self { code, modifiers: modifiers - KeyModifiers::SHIFT });
+            }
+        }
+        Ok(Self { code, modifiers })
+    }
+}
+
+impl TryFrom<String> for KeyBinding {
+    type Error = String;
+
+    fn try_from(text: String) -> Result<Self, Self::Error> {
+        text.parse()
+    }
+}
+
+impl fmt::Display for KeyBinding {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        for (modifier, name) in [
+            (KeyModifiers::CONTROL, "ctrl-"),
+            (KeyModifiers::ALT, "alt-"),
+            (KeyModifiers::SHIFT, "shift-"),
+        ] {
+            if self.modifiers.contains(modifier) {
+                f.write_str(name)?;
+            }
+        }
+        match self.code {
+            KeyCode::Char(' ') => f.write_str("space"),
+            KeyCode::Char(char) => write!(f, "{char}"),
+            KeyCode::F(number) => write!(f, "f{number}"),
+            KeyCode::Esc => f.write_str("esc"),
+            KeyCode::Enter => f.write_str("enter"),
+            KeyCode::Tab => f.write_str("tab"),
+            KeyCode::BackTab => f.write_str("backtab"),
+            KeyCode::Backspace => f.write_str("backspace"),
+            KeyCode::Delete => f.write_str("delete"),
+            KeyCode::Insert => f.write_str("insert"),
+            KeyCode::Left => f.write_str("left"),
+            KeyCode::Right => f.write_str("right"),
+            KeyCode::Up => f.write_str("up"),
+            KeyCode::Down => f.write_str("down"),
+            KeyCode::Home => f.write_str("home"),
+            KeyCode::End => f.write_str("end"),
+            KeyCode::PageUp => f.write_str("pageup"),
+            KeyCode::PageDown => f.write_str("pagedown"),
+            code => write!(f, "{code:?}"),
+        }
+    }
+}
+
+/// `[keys]` section of the config: the keys of some actions, replacing their defaults.
+pub type KeymapConfig = HashMap<Action, Vec<KeyBinding>>;
+
+/// Keys of every action.
+#[derive(Debug, Clone)]
+pub struct Keymap {
+    bindings: Vec<(Action, Vec<KeyBinding>)>,
+}
+
+impl Default for Keymap {
+    fn default() -> Self {
+        Self::new(&KeymapConfig::new()).expect("default keys conflict")
+    }
+}
+
+impl Keymap {
+    /// Builds the default keymap with the keys of the actions in `config` replaced.
+    ///
+    /// Fails if `config` gives two actions the same key, as only one of them could be triggered.
+    pub fn new(config: &KeymapConfig) -> Result<Self, String> {
+        let mut bindings: Vec<_> = Action::ALL
+            .iter()
+            .map(|&action| {
+                let keys = config.get(&action).cloned().unwrap_or_else(|| {
+                    action.default_keys().iter().map(|key| key.parse().unwrap()).collect()
+                });
+                (action, keys)
+            })
+            .collect();
+        // A key moved to another action in the config must not keep doing its default.
+        let configured: Vec<KeyBinding> = config.values().flatten().copied().collect();
+        for (action, keys) in &mut bindings {
+            if !config.contains_key(action) {
+                keys.retain(|key| !configured.contains(key));
+            }
+        }
+        for (index, (action, keys)) in bindings.iter().enumerate() {
+            for (other, other_keys) in &bindings[index + 1..] {
+                if let Some(key) = keys.iter().find(|key| other_keys.contains(key)) {
+                    return Err(format!("key `{key}` is bound to both `{action}` and `{other}`"));
+                }
+            }
+        }
+        Ok(Self { bindings })
+    }
+
+    /// The action bound to `event`.
+    pub fn action(&self, event: KeyEvent) -> Option<Action> {
+        self.bindings
+            .iter()
+            .find(|(_, keys)| keys.iter().any(|key| key.matches(event)))
+            .map(|&(action, _)| action)
+    }
+
+    /// Keys bound to `action`.
+    pub fn keys(&self, action: Action) -> &[KeyBinding] {
+        self.bindings
+            .iter()
+            .find(|&&(bound, _)| bound == action)
+            .map_or(&[], |(_, keys)| keys.as_slice())
+    }
+
+    /// First key of `action` for hints, e.g. `q` in `q: quit`.
+    pub fn hint(&self, action: Action) -> String {
+        self.keys(action).first().map_or_else(|| "unbound".to_string(), ToString::to_string)
+    }
+}
@@ -0,0 +1,410 @@
+use ratatui::layout::{Constraint, Direction, Layout, Rect};
+use serde::Deserialize;
+
+use crate::keymap::Action;
+
+/// A widget that can be placed in the dashboard.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum Panel {
+    ECpu,
+    PCpu,
+    Gpu,
+    Ane,
+    SocInfo,
+    NetDisk,
+    PowerList,
+    PowerChart,
+    Memory,
+    Processes,
+    /// Activity and frequency of every CPU cluster.
+    CpuClusters,
+    /// Activity and frequency of every core.
+    CpuCores,
+    GpuInfo,
+    /// Session energy, cost and emissions per rail and per process.
+    Energy,
+    /// Processes by energy used over the stats window, including exited ones.
+    TopConsumers,
+    /// Active alerts, most severe first.
+    Alerts,
+    /// E-CPU and P-CPU usage over time.
+    CpuChart,
+    GpuChart,
+    /// E-CPU, P-CPU and GPU frequencies over time.
+    FreqChart,
+    /// CPU, GPU and ANE power over time.
+    RailsChart,
+    /// Memory and swap used over time.
+    MemoryChart,
+    NetChart,
+    DiskChart,
+}
+
+impl Panel {
+    pub const ALL: [Panel; 23] = [
+        Panel::ECpu,
+        Panel::PCpu,
+        Panel::Gpu,
+        Panel::Ane,
+        Panel::SocInfo,
+        Panel::NetDisk,
+        Panel::PowerList,
+        Panel::PowerChart,
+        Panel::Memory,
+        Panel::Processes,
+        Panel::CpuClusters,
+        Panel::CpuCores,
+        Panel::GpuInfo,
+        Panel::Energy,
+        Panel::TopConsumers,
+        Panel::Alerts,
+        Panel::CpuChart,
+        Panel::GpuChart,
+        Panel::FreqChart,
+        Panel::RailsChart,
+        Panel::MemoryChart,
+        Panel::NetChart,
+        Panel::DiskChart,
+    ];
+
+    pub fn title(self) -> &'static str {
+        match self {
+            Panel::ECpu => "E-CPU",
+            Panel::PCpu => "P-CPU",
+            Panel::Gpu => "GPU",
+            Panel::Ane => "ANE",
+            Panel::SocInfo => "SoC",
+            Panel::NetDisk => "Network & Disk",
+            Panel::PowerList => "Power",
+            Panel::PowerChart => "Power Chart",
+            Panel::Memory => "Memory",
+            Panel::Processes => "Processes",
+            Panel::CpuClusters => "Clusters",
+            Panel::CpuCores => "Cores",
+            Panel::GpuInfo => "GPU Info",
+            Panel::Energy => "Energy",
+            Panel::TopConsumers => "Top Consumers",
+            Panel::Alerts => "Alerts",
+            Panel::CpuChart => "CPU Chart",
+            Panel::GpuChart => "GPU Chart",
+            Panel::FreqChart => "Frequency Chart",
+            Panel::RailsChart => "Rails Chart",
+            Panel::MemoryChart => "Memory Chart",
+            Panel::NetChart => "Network Chart",
+            Panel::DiskChart => "Disk Chart",
+        }
+    }
+
+    /// Does the panel only show metrics read from `powermetrics`?
+    pub fn needs_powermetrics(self) -> bool {
+        matches!(
+            self,
+            Panel::Gpu
+                | Panel::Ane
+                | Panel::PowerList
+                | Panel::PowerChart
+                | Panel::Processes
+                | Panel::CpuClusters
+                | Panel::GpuInfo
+                | Panel::Energy
+                | Panel::TopConsumers
+                | Panel::GpuChart
+                | Panel::FreqChart
+                | Panel::RailsChart
+                | Panel::DiskChart
+        )
+    }
+}
+
+/// A view of the selected host.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum Tab {
+    /// The layout of the config.
+    #[default]
+    Overview,
+    Cpu,
+    Gpu,
+    Power,
+    Processes,
+    NetDisk,
+}
+
+impl Tab {
+    pub const ALL: [Tab; 6] =
+        [Tab::Overview, Tab::Cpu, Tab::Gpu, Tab::Power, Tab::Processes, Tab::NetDisk];
+
+    pub fn title(self) -> &'static str {
+        match self {
+            Tab::Overview => "Overview",
+            Tab::Cpu => "CPU",
+            Tab::Gpu => "GPU",
+            Tab::Power => "Power",
+            Tab::Processes => "Processes",
+            Tab::NetDisk => "Net/Disk",
+        }
+    }
+
+    /// The action showing this tab.
+    pub fn action(self) -> Action {
+        match self {
+            Tab::Overview => Action::TabOverview,
+            Tab::Cpu => Action::TabCpu,
+            Tab::Gpu => Action::TabGpu,
+            Tab::Power => Action::TabPower,
+            Tab::Processes => Action::TabProcesses,
+            Tab::NetDisk => Action::TabNetDisk,
+        }
+    }
+
+    pub fn index(self) -> usize {
+        Tab::ALL.iter().position(|&tab| tab == self).unwrap_or_default()
+    }
+
+    pub fn next(self) -> Self {
+        Tab::ALL[(self.index() + 1) % Tab::ALL.len()]
+    }
+
+    pub fn previous(self) -> Self {
+        Tab::ALL[(self.index() + Tab::ALL.len() - 1) % Tab::ALL.len()]
+    }
+
+    /// Panels of this tab, `overview` being the layout of the config.
+    pub fn layout(self, overview: &LayoutConfig) -> LayoutConfig {
+        let column = |panel, size| ColumnConfig { panel, size };
+        let row = |size, columns| RowConfig { size, columns };
+        let rows = match self {
+            Tab::Overview => return overview.clone(),
+            Tab::Cpu => vec![
+                row(
+                    Size::Length(3),
+                    vec![
+                        column(Panel::ECpu, Size::Percentage(50)),
+                        column(Panel::PCpu, Size::Percentage(50)),
+                    ],
+                ),
+                row(
+                    Size::Min(0),
+                    vec![
+                        column(Panel::CpuClusters, Size::Length(40)),
+                        column(Panel::CpuCores, Size::Min(0)),
+                    ],
+                ),
+                row(
+                    Size::Percentage(50),
+                    vec![
+                        column(Panel::CpuChart, Size::Percentage(50)),
+                        column(Panel::FreqChart, Size::Percentage(50)),
+                    ],
+                ),
+            ],
+            Tab::Gpu => vec![
+                row(Size::Length(3), vec![column(Panel::Gpu, Size::Percentage(100))]),
+                row(
+                    Size::Min(0),
+                    vec![
+                        column(Panel::GpuInfo, Size::Length(40)),
+                        column(Panel::GpuChart, Size::Min(0)),
+                    ],
+                ),
+                row(
+                    Size::Percentage(50),
+                    vec![
+                        column(Panel::FreqChart, Size::Percentage(50)),
+                        column(Panel::RailsChart, Size::Percentage(50)),
+                    ],
+                ),
+            ],
+            Tab::Power => vec![
+                row(
+                    Size::Percentage(40),
+                    vec![
+                        column(Panel::PowerList, Size::Length(40)),
+                        column(Panel::PowerChart, Size::Min(0)),
+                    ],
+                ),
+                row(
+                    Size::Percentage(30),
+                    vec![
+                        column(Panel::RailsChart, Size::Percentage(50)),
+                        column(Panel::TopConsumers, Size::Percentage(50)),
+                    ],
+                ),
+                row(Size::Min(0), vec![column(Panel::Energy, Size::Percentage(100))]),
+            ],
+            Tab::Processes => {
+                vec![row(Size::Min(0), vec![column(Panel::Processes, Size::Percentage(100))])]
+            },
+            Tab::NetDisk => vec![
+                row(Size::Length(6), vec![column(Panel::NetDisk, Size::Percentage(100))]),
+                row(Size::Min(0), vec![column(Panel::NetChart, Size::Percentage(100))]),
+                row(Size::Percentage(40), vec![column(Panel::DiskChart, Size::Percentage(100))]),
+            ],
+        };
+        LayoutConfig { rows }
+    }
+}
+
+/// Height of a row or width of a column.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(try_from = "SizeRepr")]
+pub enum Size {
+    /// `"25%"` of the available space.
+    Percentage(u16),
+    /// `12` cells.
+    Length(u16),
+    /// `"min:8"`, at least 8 cells and whatever is left.
+    Min(u16),
+}
+
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum SizeRepr {
+    Cells(u16),
+    Text(String),
+}
+
+impl TryFrom<SizeRepr> for Size {
+    type Error = String;
+
+    fn try_from(repr: SizeRepr) -> Result<Self, Self::Error> {
+        let text = match repr {
+            SizeRepr::Cells(cells) => return Ok(Size::Length(cells)),
+            SizeRepr::Text(text) => text,
+        };
+        let text = text.trim();
+        let parse = |number: &str| {
+            number.trim().parse::<u16>().map_err(|_| {
+                format!("invalid size `{text}`, expected e.g. \"25%\", 12 or \"min:8\"")
+            })
+        };
+        if let Some(percent) = text.strip_suffix('%') {
+            let percent = parse(percent)?;
+            if percent > 100 {
+                return Err(format!("invalid size `{text}`, percentages go up to 100"));
+            }
+            Ok(Size::Percentage(percent))
+        } else if let Some(min) = text.strip_prefix("min:") {
+            Ok(Size::Min(parse(min)?))
+        } else {
+            Ok(Size::Length(parse(text)?))
+        }
+    }
+}
+
+impl From<Size> for Constraint {
+    fn from(size: Size) -> Self {
+        match size {
+            Size::Percentage(percent) => Constraint::Percentage(percent),
+            Size::Length(cells) => Constraint::Length(cells),
+            Size::Min(cells) => Constraint::Min(cells),
+        }
+    }
+}
+
+/// A panel and its width within a row.
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct ColumnConfig {
+    pub panel: Panel,
+    pub size: Size,
+}
+
+/// A horizontal band of panels.
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct RowConfig {
+    pub size: Size,
+    pub columns: Vec<ColumnConfig>,
+}
+
+/// Rows of panels of the single-host dashboard, from top to bottom.
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct LayoutConfig {
+    pub rows: Vec<RowConfig>,
+}
+
+/// Dense layout, one line per metric, used instead of the tabs in small terminals.
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct CompactConfig {
+    pub enabled: bool,
+    /// The compact layout is used when the terminal is narrower than this...
+    pub width: u16,
+    /// ...or shorter than this.
+    pub height: u16,
+}
+
+impl Default for CompactConfig {
+    fn default() -> Self {
+        Self { enabled: true, width: 80, height: 24 }
+    }
+}
+
+impl CompactConfig {
+    /// Is `area` small enough for the compact layout?
+    pub fn applies(&self, area: Rect) -> bool {
+        self.enabled && (area.width < self.width || area.height < self.height)
+    }
+}
+
+impl Default for LayoutConfig {
+    /// Four equal rows: E-CPU and GPU, P-CPU and ANE, the info lists and the power chart, memory.
+    fn default() -> Self {
+        let row = |columns: &[Panel]| RowConfig {
+            size: Size::Percentage(25),
+            columns: columns
+                .iter()
+                .map(|&panel| ColumnConfig {
+                    panel,
+                    size: Size::Percentage(100 / columns.len() as u16),
+                })
+                .collect(),
+        };
+        Self {
+            rows: vec![
+                row(&[Panel::ECpu, Panel::Gpu]),
+                row(&[Panel::PCpu, Panel::Ane]),
+                row(&[Panel::SocInfo, Panel::NetDisk, Panel::PowerList, Panel::PowerChart]),
+                row(&[Panel::Memory]),
+            ],
+        }
+    }
+}
+
+impl LayoutConfig {
+    /// Checks that every row has panels.
+    pub fn validate(&self) -> Result<(), String> {
+        if self.rows.is_empty() {
+            return Err("layout has no rows".to_string());
+        }
+        match self.rows.iter().position(|row| row.columns.is_empty()) {
+            Some(index) => Err(format!("layout row {} has no columns", index + 1)),
+            None => Ok(()),
+        }
+    }
+
+    /// Splits `area` into the rectangle of every panel.
+    pub fn split(&self, area: Rect) -> Vec<(Panel, Rect)> {
+        let rows = Layout::default()
+            .direction(Direction::Vertical)
+            .constraints(self.rows.iter().map(|row| Constraint::from(row.size)))
+            .split(area);
+        self.rows
+            .iter()
+            .zip(rows.iter())
+            .flat_map(|(row, &row_area)| {
+                let columns = Layout::default()
+                    .direction(Direction::Horizontal)
+                    .constraints(row.columns.iter().map(|column| Constraint::from(column.size)))
+                    .split(row_area);
+                row.columns
+                    .iter()
+                    .zip(columns.iter())
+                    .map(|(column, &column_area)| (column.panel, column_area))
+                    .collect::<Vec<_>>()
+            })
+            .collect()
+    }
+}
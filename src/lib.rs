@@ -1,6 +1,36 @@
 /// Application.
 pub mod app;
 
+/// Settings file.
+pub mod config;
+
+/// Dashboard layout.
+pub mod layout;
+
+/// Colors of the interface.
+pub mod theme;
+
+/// Time series of every metric.
+pub mod history;
+
+/// On-disk history.
+pub mod store;
+
+/// Windowed statistics over the history.
+pub mod stats;
+
+/// Energy integrated from the power rails.
+pub mod energy;
+
+/// Threshold alerts.
+pub mod alert;
+
+/// Commands and webhooks run when alerts change.
+pub mod hook;
+
+/// Per-host state.
+pub mod host;
+
 /// Terminal events handler.
 pub mod event;
 
@@ -13,4 +43,22 @@ pub mod tui;
 /// Event handler.
 pub mod handler;
 
+/// Configurable key bindings.
+pub mod keymap;
+
 pub mod metrics;
+
+/// Chip, cores and OS of the machine.
+pub mod soc;
+
+/// Peak frequencies and powers of known chips.
+pub mod chip;
+
+/// Command line arguments.
+pub mod cli;
+
+/// Agent serving metrics over TCP and its client.
+pub mod remote;
+
+/// Getting the root powermetrics needs.
+pub mod privilege;
@@ -1,43 +1,70 @@
 use mactop_rs::app::{App, AppResult};
-use mactop_rs::event::{Event, EventHandler};
-use mactop_rs::handler::handle_key_events;
+use mactop_rs::cli::{Args, Mode};
+use mactop_rs::config::Config;
+use mactop_rs::event::{Event, EventHandler, Source};
+use mactop_rs::handler::{handle_key_events, handle_mouse_events};
+use mactop_rs::privilege::{self, Escalate};
+use mactop_rs::remote;
 use mactop_rs::tui::Tui;
 use ratatui::backend::CrosstermBackend;
 use ratatui::Terminal;
 use std::io;
 
 fn main() -> AppResult<()> {
+    let args = Args::from_env()?;
+    let config = Config::load(args.config.as_deref())?;
     // Create an application.
-    let mut app = App::new();
+    let (mut app, sources) = match args.mode {
+        Mode::Agent(addr) => return remote::serve(&addr),
+        Mode::Helper => return remote::serve_stdio(),
+        Mode::Local => {
+            // Asked before the alternate screen, so prompts and passwords can be typed.
+            let source = match privilege::choose(&config.privileges) {
+                Escalate::Sudo => return privilege::reexec_with_sudo(args.config.as_deref()),
+                Escalate::Helper => match privilege::authenticate() {
+                    Ok(()) => Source::Helper,
+                    Err(err) => {
+                        eprintln!("{err}, continuing without root");
+                        Source::Local
+                    },
+                },
+                Escalate::Ask | Escalate::Never => Source::Local,
+            };
+            (App::new(config), vec![source])
+        },
+        Mode::Remote(addrs) => {
+            (App::remote(addrs.clone(), config), addrs.into_iter().map(Source::Remote).collect())
+        },
+    };
+    for err in app.open_stores() {
+        eprintln!("{err}");
+    }
     // Initialize the terminal user interface.
     let backend = CrosstermBackend::new(io::stderr());
     let terminal = Terminal::new(backend)?;
-    let events = EventHandler::new(2500);
+    let events = EventHandler::new(2500, sources);
     let mut tui = Tui::new(terminal, events);
     tui.init()?;
     // Start the main loop.
     while app.running {
-        // app.collect_metrics();
         // Render the user interface.
         tui.draw(&mut app)?;
-        // println!("{:?}", app.cpu_metrics);
         // Handle events.
         match tui.events.next()? {
             Event::Tick => {}
             Event::Key(key_event) => handle_key_events(key_event, &mut app)?,
-            Event::Mouse(_) => {}
+            Event::Mouse(mouse_event) => handle_mouse_events(mouse_event, &mut app)?,
             Event::Resize(_, _) => {}
-            Event::Metrics(metrics) => {
-                app.cpu_w.push(metrics.cpu_metrics.package_w.to_owned());
-                if app.cpu_w.len() > 25 {
-                    app.cpu_w.remove(0);
-                }
-                app.metrics = metrics;
-            }
+            Event::Metrics(index, metrics) => app.on_metrics(index, *metrics),
+            Event::Connection(index, state) => app.on_connection(index, state),
         }
     }
 
     // Exit the user interface.
     tui.exit()?;
+    for host in app.hosts.iter().filter(|host| host.has_metrics()) {
+        println!("{}", host.name());
+        print!("{}", host.energy.summary(&app.config.tariff));
+    }
     Ok(())
 }
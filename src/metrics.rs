@@ -1,15 +1,36 @@
 use std::{
-    cmp::max,
-    collections::HashMap,
-    io::{BufReader, Read},
+    io,
     process::{Command, Stdio},
+    thread,
+    time::{Duration, Instant, SystemTime, UNIX_EPOCH},
 };
 
-use psutil::memory::{swap_memory, virtual_memory};
+use psutil::{
+    cpu::CpuPercentCollector,
+    memory::{swap_memory, virtual_memory},
+    network::NetIoCountersCollector,
+};
 use regex::Regex;
+use serde::{Deserialize, Serialize};
+
+use crate::soc::SocInfo;
+
+/// Per-core lines with a higher CPU number are ignored.
+const MAX_CORES: usize = 256;
+
+/// Time covered by one sample.
+const INTERVAL: Duration = Duration::from_millis(1000);
 
-#[derive(Debug, Clone)]
+/// A single sample of everything mactop-rs displays.
+///
+/// Snapshots are plain data so they can be sent to remote clients. Missing fields default, so
+/// agents and clients of different versions can talk to each other.
+#[derive(Debug, Default, Clone, Serialize, Deserialize)]
+#[serde(default)]
 pub struct Metrics {
+    /// When the sample was taken, in seconds since the Unix epoch.
+    pub timestamp: f64,
+
     /// mem
     pub mem: MemoryMetrics,
 
@@ -22,14 +43,79 @@ pub struct Metrics {
     /// net disk metrics
     pub net_disk_metrics: NetDiskMetrics,
 
-    // /// process metrics
-    // pub process_metrics: ProcessMetrics,
+    /// process metrics
+    pub processes: Vec<ProcessMetrics>,
+
     /// os info
-    pub soc_info: HashMap<String, String>,
+    pub soc_info: SocInfo,
+
+    /// Thermal pressure level, e.g. `Nominal`, empty if not reported
+    pub thermal_pressure: String,
+
+    /// Why `powermetrics` couldn't run, only the metrics readable without it are then filled in
+    pub degraded: Option<Degraded>,
+}
+
+/// Why a sample was taken without `powermetrics`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Degraded {
+    /// `powermetrics` isn't on the `PATH`, e.g. not on macOS.
+    Missing,
+    /// `powermetrics` only runs as root.
+    NotRoot,
+    /// `powermetrics` failed, with its error.
+    Failed(String),
+    /// The root helper collecting with `powermetrics` stopped, with its exit status or error.
+    HelperFailed(String),
+}
+
+impl Degraded {
+    /// Short description, e.g. for a status line.
+    pub fn summary(&self) -> &str {
+        match self {
+            Self::Missing => "powermetrics not found",
+            Self::NotRoot => "not running as root",
+            Self::Failed(_) => "powermetrics failed",
+            Self::HelperFailed(_) => "root helper failed",
+        }
+    }
+}
 
+/// Runs `powermetrics` and parses its output into [`Metrics`].
+#[derive(Debug)]
+pub struct Collector {
+    /// os info, queried once
+    soc_info: SocInfo,
+
+    parser: Parser,
+
+    /// Set once `powermetrics` is known never to run in this process.
+    unavailable: Option<Degraded>,
+
+    /// Unprivileged sources used instead of `powermetrics`.
+    fallback: Fallback,
+}
+
+/// Metrics read without `powermetrics` nor root: CPU usage per core and network traffic.
+#[derive(Debug)]
+struct Fallback {
+    cpu: Option<CpuPercentCollector>,
+    net: NetIoCountersCollector,
+
+    /// Bytes and packets received and sent so far, and when they were read
+    last_net: Option<([u64; 4], Instant)>,
+}
+
+/// Parses the text output of `powermetrics`.
+///
+/// The output changes with chips and macOS versions, so lines that don't look as expected are
+/// skipped: unknown or truncated output gives partial metrics, never a panic.
+#[derive(Debug)]
+pub struct Parser {
     /// regex
     residency_re: Regex,
     frequency_re: Regex,
+    core_re: Regex,
     re: Regex,
     out_re: Regex,
     in_re: Regex,
@@ -38,7 +124,7 @@ pub struct Metrics {
     data_re: Regex,
 }
 
-#[derive(Debug, Default, Clone)]
+#[derive(Debug, Default, Clone, Serialize, Deserialize)]
 pub struct MemoryMetrics {
     pub total: u64,
     pub used: u64,
@@ -48,28 +134,32 @@ pub struct MemoryMetrics {
 }
 
 impl MemoryMetrics {
-    pub fn update(&mut self) {
-        let v = virtual_memory().unwrap();
-        let s = swap_memory().unwrap();
+    /// Reads the memory and swap usage, leaving every field as it was if either fails.
+    pub fn update(&mut self) -> psutil::Result<()> {
+        let v = virtual_memory()?;
+        let s = swap_memory()?;
 
         self.total = v.total();
         self.used = v.used();
         self.available = v.available();
         self.swap_total = s.total();
         self.swap_used = s.used();
+        Ok(())
     }
 }
 
-#[derive(Debug, Default, Clone)]
+#[derive(Debug, Default, Clone, Serialize, Deserialize)]
 pub struct CPUMetrics {
     pub e_cluster_active: i64,
     pub e_cluster_freq_mhz: i64,
     pub p_cluster_active: i64,
     pub p_cluster_freq_mhz: i64,
 
-    // e_cores: Vec<i8>,
-    // p_cores: Vec<i8>,
+    /// Active residency of every core in percent, indexed by CPU number
     pub cores: Vec<i64>,
+    /// Frequency of every core, indexed by CPU number
+    #[serde(default)]
+    pub core_freqs_mhz: Vec<i64>,
 
     pub ane_w: f64,
     pub cpu_w: f64,
@@ -90,7 +180,7 @@ pub struct CPUMetrics {
     // p3_cluster_freq_mhz: i64,
 }
 
-#[derive(Debug, Default, Clone)]
+#[derive(Debug, Default, Clone, Serialize, Deserialize)]
 pub struct NetDiskMetrics {
     pub out_packets_per_sec: f64,
     pub out_bytes_per_sec: f64,
@@ -102,27 +192,162 @@ pub struct NetDiskMetrics {
     pub write_k_bytes_per_sec: f64,
 }
 
-#[derive(Debug, Default, Clone)]
+#[derive(Debug, Default, Clone, Serialize, Deserialize)]
 pub struct GPUMetrics {
     pub freq_mhz: i64,
     pub active: f64,
 }
 
-// #[derive(Debug, Default, Clone)]
-// pub struct ProcessMetrics {
-//     id: i64,
-//     name: String,
-//     cpu_usage: f64,
-// }
+#[derive(Debug, Default, Clone, Serialize, Deserialize)]
+pub struct ProcessMetrics {
+    pub pid: i64,
+    pub name: String,
+    /// CPU time used per second of wall time, 1000 ms/s is one fully busy core
+    pub cpu_ms_per_s: f64,
+    pub energy_impact: f64,
+}
+
+impl ProcessMetrics {
+    /// CPU usage in percent of one core.
+    pub fn cpu_percent(&self) -> f64 {
+        self.cpu_ms_per_s / 10.0
+    }
+}
 
-impl Metrics {
+impl Collector {
+    pub fn new() -> Self {
+        Self { soc_info: SocInfo::detect(),
+               parser: Parser::new(),
+               unavailable: None,
+               fallback: Fallback::new() }
+    }
+
+    /// Takes one sample, which blocks for the sample interval.
+    ///
+    /// Without `powermetrics` the sample only has the metrics of [`Fallback`], and says why in
+    /// [`Metrics::degraded`].
+    pub fn collect(&mut self) -> Metrics {
+        let mut metrics = Metrics { soc_info: self.soc_info.clone(), ..Default::default() };
+        match self.powermetrics() {
+            Ok(info) => self.parser.parse(&info, &mut metrics),
+            Err(degraded) => {
+                // powermetrics returns after the interval, keep the same pace without it
+                thread::sleep(INTERVAL);
+                self.fallback.update(&mut metrics);
+                metrics.degraded = Some(degraded);
+            },
+        }
+        // Empty memory gauges are better than no sample at all
+        let _ = metrics.mem.update();
+        metrics.timestamp = now();
+        metrics
+    }
+
+    /// Runs `powermetrics` for one sample and returns its output.
+    fn powermetrics(&mut self) -> Result<String, Degraded> {
+        if let Some(degraded) = &self.unavailable {
+            return Err(degraded.clone());
+        }
+        let output =
+            Command::new("powermetrics").args(["--samplers",
+                                               "cpu_power,gpu_power,thermal,network,disk,tasks",
+                                               "--show-process-gpu",
+                                               "--show-process-energy",
+                                               "--show-initial-usage",
+                                               "--show-process-netstats",
+                                               "-n 1",
+                                               "-i 1000"])
+                                        .stdin(Stdio::null())
+                                        .output();
+        let output = match output {
+            Ok(output) => output,
+            Err(err) if err.kind() == io::ErrorKind::NotFound => {
+                self.unavailable = Some(Degraded::Missing);
+                return Err(Degraded::Missing);
+            },
+            Err(err) => return Err(Degraded::Failed(err.to_string())),
+        };
+        if !output.status.success() {
+            // It refuses to start unless run by root, which won't change while we run
+            if !is_root() {
+                self.unavailable = Some(Degraded::NotRoot);
+                return Err(Degraded::NotRoot);
+            }
+            let stderr = String::from_utf8_lossy(&output.stderr);
+            let error = stderr.lines().find(|line| !line.trim().is_empty());
+            return Err(Degraded::Failed(error.map_or_else(|| output.status.to_string(),
+                                                          |line| line.trim().to_string())));
+        }
+        let info = String::from_utf8_lossy(&output.stdout).into_owned();
+        if info.trim().is_empty() {
+            return Err(Degraded::Failed("powermetrics printed nothing".to_string()));
+        }
+        Ok(info)
+    }
+}
+
+impl Fallback {
+    /// Starts measuring, the first update covering the time since.
+    fn new() -> Self {
+        let mut fallback = Self { cpu: CpuPercentCollector::new().ok(),
+                                  net: NetIoCountersCollector::default(),
+                                  last_net: None };
+        fallback.update(&mut Metrics::default());
+        fallback
+    }
+
+    /// Fills in CPU usage per core, averaged per cluster kind, and network rates.
+    fn update(&mut self, metrics: &mut Metrics) {
+        if let Some(percents) = self.cpu.as_mut().and_then(|cpu| cpu.cpu_percent_percpu().ok()) {
+            let cpu_metrics = &mut metrics.cpu_metrics;
+            cpu_metrics.cores = percents.iter().map(|&percent| percent as i64).collect();
+            // Apple silicon numbers efficiency cores first
+            let e_cores = (metrics.soc_info.e_core_count as usize).min(cpu_metrics.cores.len());
+            let (e, p) = cpu_metrics.cores.split_at(e_cores);
+            cpu_metrics.e_cluster_active = mean(e);
+            cpu_metrics.p_cluster_active = mean(p);
+        }
+
+        if let Ok(counters) = self.net.net_io_counters() {
+            let totals = [counters.bytes_recv(),
+                          counters.bytes_sent(),
+                          counters.packets_recv(),
+                          counters.packets_sent()];
+            let now = Instant::now();
+            if let Some((last, time)) = self.last_net {
+                let seconds = now.duration_since(time).as_secs_f64().max(f64::EPSILON);
+                let rate = |index: usize| totals[index].saturating_sub(last[index]) as f64 / seconds;
+                let net = &mut metrics.net_disk_metrics;
+                (net.in_bytes_per_sec, net.out_bytes_per_sec) = (rate(0), rate(1));
+                (net.in_packets_per_sec, net.out_packets_per_sec) = (rate(2), rate(3));
+            }
+            self.last_net = Some((totals, now));
+        }
+    }
+}
+
+/// Mean of `values`, 0 if empty.
+fn mean(values: &[i64]) -> i64 {
+    match values.len() {
+        0 => 0,
+        count => values.iter().sum::<i64>() / count as i64,
+    }
+}
+
+/// Is this process running as root?
+pub fn is_root() -> bool {
+    // SAFETY: `geteuid` has no preconditions and always succeeds.
+    unsafe { libc::geteuid() == 0 }
+}
+
+impl Parser {
     pub fn new() -> Self {
         Self {
-            soc_info: get_soc_info(),
             residency_re: Regex::new(r"(\w+-Cluster)\s+HW active residency:\s+(\d+\.\d+)%")
                 .unwrap(),
             frequency_re: Regex::new(r"(\w+-Cluster)\s+HW active frequency:\s+(\d+)\s+MHz")
                 .unwrap(),
+            core_re: Regex::new(r"^CPU (\d+) (frequency|active residency):\s+(\d+(\.\d+)?)").unwrap(),
             re: Regex::new(r"GPU\s*(HW)?\s*active\s*(residency|frequency):\s+(\d+(\.)?(\d+)?)%?")
                 .unwrap(),
             out_re: Regex::new(r"out:\s*([\d.]+)\s*packets/s,\s*([\d.]+)\s*bytes/s").unwrap(),
@@ -130,323 +355,215 @@ impl Metrics {
             read_re: Regex::new(r"read:\s*([\d.]+)\s*ops/s\s*([\d.]+)\s*KBytes/s").unwrap(),
             write_re: Regex::new(r"write:\s*([\d.]+)\s*ops/s\s*([\d.]+)\s*KBytes/s").unwrap(),
             data_re: Regex::new(r"(?m)^\s*(\S.*?)\s+(\d+)\s+(\d+\.\d+)\s+\d+\.\d+\s+").unwrap(),
-            mem: MemoryMetrics::default(),
-            cpu_metrics: CPUMetrics::default(),
-            gpu_metrics: GPUMetrics::default(),
-            net_disk_metrics: NetDiskMetrics::default(),
-            // process_metrics: ProcessMetrics::default(),
         }
     }
 
-    pub fn collect_metrics(&mut self) {
-        let mut child =
-            Command::new("powermetrics").args(["--samplers",
-                                               "cpu_power,gpu_power,thermal,network,disk",
-                                               "--show-process-gpu",
-                                               "--show-process-energy",
-                                               "--show-initial-usage",
-                                               "--show-process-netstats",
-                                               "-n 1",
-                                               "-i 1000"])
-                                        .stdout(Stdio::piped())
-                                        .spawn()
-                                        .unwrap();
-        if let Some(stdout) = child.stdout.take() {
-            let mut reader = BufReader::new(stdout);
-            let mut info = String::new();
-            reader.read_to_string(&mut info).unwrap();
-
-            self.parse_cpu_metrics(&info);
-            self.parse_gpu_metrics(&info);
-            self.parse_activity_metrics(&info);
-            self.parse_process_metrics(&info);
+    /// Parses the text output of `powermetrics` into `metrics`.
+    pub fn parse(&self, info: &str, metrics: &mut Metrics) {
+        self.parse_cpu_metrics(info, &mut metrics.cpu_metrics);
+        self.parse_gpu_metrics(info, &mut metrics.gpu_metrics);
+        self.parse_activity_metrics(info, &mut metrics.net_disk_metrics);
+        self.parse_process_metrics(info, &mut metrics.processes);
+        self.parse_thermal_pressure(info, &mut metrics.thermal_pressure);
+    }
+
+    pub fn parse_thermal_pressure(&self, info: &str, thermal_pressure: &mut String) {
+        if let Some(level) = info.lines()
+                                 .find_map(|line| line.trim().strip_prefix("Current pressure level:"))
+        {
+            *thermal_pressure = level.trim().to_string();
         }
-        self.mem.update();
     }
 
-    fn parse_gpu_metrics(&mut self, info: &str) {
-        info.split('\n')
-            .filter(|line| line.contains("GPU active") || line.contains("GPU HW active"))
-            .map(|line| self.re.captures(line))
-            .for_each(|captures| {
-                if let Some(matches) = captures {
-                    // println!("{},{}", &matches[2], &matches[3]);
-                    if &matches[2] == "frequency" {
-                        self.gpu_metrics.freq_mhz = matches[3].parse::<i64>().unwrap();
-                    } else if &matches[2] == "residency" {
-                        self.gpu_metrics.active = matches[3].parse::<f64>().unwrap();
-                    }
-                }
+    pub fn parse_gpu_metrics(&self, info: &str, gpu_metrics: &mut GPUMetrics) {
+        info.lines()
+            .filter_map(|line| self.re.captures(line))
+            .for_each(|matches| match (&matches[2], number(&matches[3])) {
+                ("frequency", Some(value)) => gpu_metrics.freq_mhz = value as i64,
+                ("residency", Some(value)) => gpu_metrics.active = value,
+                _ => {},
             });
     }
 
-    fn parse_cpu_metrics(&mut self, info: &str) {
-        // let mut e_cores: Vec<i64> = vec![];
-        // let mut p_cores: Vec<i64> = vec![];
-        let mut e_cluster_active_total = 0i64;
-        let mut e_cluster_count = 0i64;
-        let mut p_cluster_active_total = 0i64;
-        let mut p_cluster_count = 0i64;
-
-        let mut e_cluster_freq_total = 0i64;
-        info.split('\n')
-            .map(|line| (self.residency_re.captures(line), self.frequency_re.captures(line), line))
-            .for_each(|(residency_opt, frequency_opt, line)| {
-                if let Some(residency) = residency_opt {
-                    let cluster = &residency[1];
-                    let value = residency[2].parse::<f64>().unwrap() as i64;
-                    match cluster {
-                        "E0-Cluster" => {
-                            // println!("{}", &residency[2]);
-                            self.cpu_metrics.e0_cluster_active = value;
-                        },
-                        "E1-Cluster" => {
-                            self.cpu_metrics.e1_cluster_active = value;
-                        },
-                        "P0-Cluster" => {
-                            self.cpu_metrics.p0_cluster_active = value;
-                        },
-                        "P1-Cluster" => {
-                            self.cpu_metrics.p1_cluster_active = value;
-                        },
-                        "P2-Cluster" => {
-                            self.cpu_metrics.p2_cluster_active = value;
-                        },
-                        _ => {
-                            // println!("{}", &residency[1]);
-                        },
-                    }
-                    if cluster.starts_with('E') {
-                        e_cluster_active_total += value;
-                        e_cluster_count += 1;
-                    } else if cluster.starts_with('P') {
-                        p_cluster_active_total += value;
-                        p_cluster_count += 1;
-                        self.cpu_metrics.p_cluster_active = p_cluster_active_total / p_cluster_count
-                    }
+    pub fn parse_cpu_metrics(&self, info: &str, cpu_metrics: &mut CPUMetrics) {
+        // Name, active residency and frequency of every cluster, e.g. `E-Cluster` on an M1 or
+        // `P3-Cluster` on an M1 Ultra.
+        let mut clusters: Vec<(String, i64, i64)> = vec![];
+        for line in info.lines() {
+            if let Some(residency) = self.residency_re.captures(line) {
+                if let Some(value) = number(&residency[2]) {
+                    cluster(&mut clusters, &residency[1]).1 = value as i64;
                 }
-
-                if let Some(frequency) = frequency_opt {
-                    let cluster = &frequency[1];
-                    let value = frequency[2].parse::<i64>().unwrap();
-                    match cluster {
-                        "E0-Cluster" => {
-                            self.cpu_metrics.e0_cluster_freq_mhz = value;
-                        },
-                        "E1-Cluster" => {
-                            self.cpu_metrics.e1_cluster_freq_mhz = value;
-                        },
-                        "P0-Cluster" => {
-                            self.cpu_metrics.p0_cluster_freq_mhz = value;
-                        },
-                        "P1-Cluster" => {
-                            self.cpu_metrics.p1_cluster_freq_mhz = value;
-                        },
-                        "P2-Cluster" => {
-                            self.cpu_metrics.p2_cluster_freq_mhz = value;
-                        },
-                        _ => {
-                            // println!("{}", &frequency[1]);
-                        },
-                    }
-                    if cluster.starts_with('E') {
-                        e_cluster_freq_total += value;
-                        self.cpu_metrics.e_cluster_freq_mhz = e_cluster_freq_total;
-                    }
+            }
+            if let Some(frequency) = self.frequency_re.captures(line) {
+                if let Some(value) = number(&frequency[2]) {
+                    cluster(&mut clusters, &frequency[1]).2 = value as i64;
                 }
+            }
 
-                if line.contains("CPU") && line.contains("frequency") {
-                    let fields: Vec<&str> = line.split(' ').collect();
-                    if fields.len() >= 5 {
-                        // TODO split e_core,p_core
-                        self.cpu_metrics.cores.clear();
-                        self.cpu_metrics.cores.push(fields[1].parse().unwrap());
-                    }
-                } else if line.contains("ANE Power") {
-                    let fields: Vec<&str> = line.split(' ').collect();
-                    if fields.len() >= 4 {
-                        // Convert mW to W
-                        self.cpu_metrics.ane_w = fields[2].parse::<f64>().unwrap() / 1000.0;
+            if let Some(core) = self.core_re.captures(line) {
+                let index = core[1].parse::<usize>().unwrap_or(MAX_CORES);
+                let value = core[3].parse::<f64>().unwrap_or_default() as i64;
+                let values = match &core[2] {
+                    "frequency" => &mut cpu_metrics.core_freqs_mhz,
+                    _ => &mut cpu_metrics.cores,
+                };
+                if index < MAX_CORES {
+                    if values.len() <= index {
+                        values.resize(index + 1, 0);
                     }
-                } else if line.contains("CPU Power") {
-                    let fields: Vec<&str> = line.split(' ').collect();
-                    if fields.len() >= 4 {
-                        // Convert mW to W
-                        self.cpu_metrics.cpu_w = fields[2].parse::<f64>().unwrap() / 1000.0;
-                    }
-                } else if line.contains("GPU Power") {
-                    let fields: Vec<&str> = line.split(' ').collect();
-                    if fields.len() >= 4 {
-                        // Convert mW to W
-                        self.cpu_metrics.gpu_w = fields[2].parse::<f64>().unwrap() / 1000.0;
-                    }
-                } else if line.contains("Combined Power (CPU + GPU + ANE)") {
-                    let fields: Vec<&str> = line.split(' ').collect();
-                    if fields.len() >= 8 {
-                        // Convert mW to W
-                        self.cpu_metrics.package_w = fields[7].parse::<f64>().unwrap() / 1000.0;
-                    }
-                }
-                // M1 Pro
-                self.cpu_metrics.p_cluster_active =
-                    (self.cpu_metrics.p0_cluster_active + self.cpu_metrics.p1_cluster_active) / 2;
-                self.cpu_metrics.p_cluster_freq_mhz =
-                    max(self.cpu_metrics.p0_cluster_freq_mhz, self.cpu_metrics.p1_cluster_freq_mhz);
-
-                if e_cluster_count > 0 {
-                    self.cpu_metrics.e_cluster_active = e_cluster_active_total / e_cluster_count;
-                }
-            });
-    }
-
-    fn parse_activity_metrics(&mut self, info: &str) {
-        info.split('\n')
-            .map(|line| {
-                (self.in_re.captures(line),
-                 self.out_re.captures(line),
-                 self.read_re.captures(line),
-                 self.write_re.captures(line))
-            })
-            .for_each(|(in_opts, out_opts, read_opts, write_opts)| {
-                if let Some(in_caps) = in_opts {
-                    // println!("in_caps:{:?}", in_caps);
-                    self.net_disk_metrics.in_packets_per_sec = in_caps[1].parse::<f64>().unwrap();
-                    self.net_disk_metrics.in_bytes_per_sec = in_caps[2].parse::<f64>().unwrap();
+                    values[index] = value;
                 }
-
-                if let Some(out_caps) = out_opts {
-                    // println!("out_caps:{:?}", out_caps);
-                    self.net_disk_metrics.out_packets_per_sec = out_caps[1].parse::<f64>().unwrap();
-                    self.net_disk_metrics.out_bytes_per_sec = out_caps[2].parse::<f64>().unwrap();
+            } else if let Some((name, watts)) = power(line) {
+                match name {
+                    "ANE Power" => cpu_metrics.ane_w = watts,
+                    "CPU Power" => cpu_metrics.cpu_w = watts,
+                    "GPU Power" => cpu_metrics.gpu_w = watts,
+                    // Older macOS, and Intel Macs which only report the package
+                    "Combined Power (CPU + GPU + ANE)"
+                    | "Package Power"
+                    | "Intel energy model derived package power (CPUs+GPU)" => {
+                        cpu_metrics.package_w = watts
+                    },
+                    _ => {},
                 }
+            }
+        }
+        for (name, active, freq_mhz) in &clusters {
+            // Chips with a single cluster of a kind don't number it
+            let fields = match name.as_str() {
+                "E-Cluster" | "E0-Cluster" => {
+                    (&mut cpu_metrics.e0_cluster_active, &mut cpu_metrics.e0_cluster_freq_mhz)
+                },
+                "E1-Cluster" => {
+                    (&mut cpu_metrics.e1_cluster_active, &mut cpu_metrics.e1_cluster_freq_mhz)
+                },
+                "P-Cluster" | "P0-Cluster" => {
+                    (&mut cpu_metrics.p0_cluster_active, &mut cpu_metrics.p0_cluster_freq_mhz)
+                },
+                "P1-Cluster" => {
+                    (&mut cpu_metrics.p1_cluster_active, &mut cpu_metrics.p1_cluster_freq_mhz)
+                },
+                "P2-Cluster" => {
+                    (&mut cpu_metrics.p2_cluster_active, &mut cpu_metrics.p2_cluster_freq_mhz)
+                },
+                _ => continue,
+            };
+            (*fields.0, *fields.1) = (*active, *freq_mhz);
+        }
 
-                if let Some(read_caps) = read_opts {
-                    self.net_disk_metrics.read_ops_per_sec = read_caps[1].parse::<f64>().unwrap();
-                    self.net_disk_metrics.read_k_bytes_per_sec =
-                        read_caps[2].parse::<f64>().unwrap();
-                }
+        // Mean residency and highest frequency over the clusters of each kind
+        for (kind, active, freq_mhz) in [('E',
+                                           &mut cpu_metrics.e_cluster_active,
+                                           &mut cpu_metrics.e_cluster_freq_mhz),
+                                          ('P',
+                                           &mut cpu_metrics.p_cluster_active,
+                                           &mut cpu_metrics.p_cluster_freq_mhz)]
+        {
+            let of_kind: Vec<_> =
+                clusters.iter().filter(|(name, ..)| name.starts_with(kind)).collect();
+            if !of_kind.is_empty() {
+                *active = of_kind.iter().map(|(_, active, _)| active).sum::<i64>()
+                          / of_kind.len() as i64;
+                *freq_mhz = of_kind.iter().map(|(.., freq_mhz)| *freq_mhz).max().unwrap_or_default();
+            }
+        }
+    }
 
-                if let Some(write_caps) = write_opts {
-                    self.net_disk_metrics.write_ops_per_sec = write_caps[1].parse::<f64>().unwrap();
-                    self.net_disk_metrics.write_k_bytes_per_sec =
-                        write_caps[2].parse::<f64>().unwrap();
+    pub fn parse_activity_metrics(&self, info: &str, net_disk_metrics: &mut NetDiskMetrics) {
+        // Count and size per second of each line
+        let mut rates = [(&self.in_re,
+                          &mut net_disk_metrics.in_packets_per_sec,
+                          &mut net_disk_metrics.in_bytes_per_sec),
+                         (&self.out_re,
+                          &mut net_disk_metrics.out_packets_per_sec,
+                          &mut net_disk_metrics.out_bytes_per_sec),
+                         (&self.read_re,
+                          &mut net_disk_metrics.read_ops_per_sec,
+                          &mut net_disk_metrics.read_k_bytes_per_sec),
+                         (&self.write_re,
+                          &mut net_disk_metrics.write_ops_per_sec,
+                          &mut net_disk_metrics.write_k_bytes_per_sec)];
+        for line in info.lines() {
+            for (re, count, size) in rates.iter_mut() {
+                if let Some(caps) = re.captures(line) {
+                    if let (Some(count_value), Some(size_value)) =
+                        (number(&caps[1]), number(&caps[2]))
+                    {
+                        (**count, **size) = (count_value, size_value);
+                    }
                 }
-            })
+            }
+        }
     }
 
-    fn parse_process_metrics(&mut self, info: &str) {
-        info.split('\n').map(|line| self.data_re.captures(line)).for_each(|data_ops| {
-                                                                    if let Some(_data_caps) =
-                                                                        data_ops
-                                                                    {
-                                                                        // println!("data: {:?}",
-                                                                        // data_caps);
-                                                                    }
-                                                                })
+    pub fn parse_process_metrics(&self, info: &str, processes: &mut Vec<ProcessMetrics>) {
+        // Only the "Running tasks" table lists processes, it ends at the next "***" header.
+        let Some((_, tasks)) = info.split_once("*** Running tasks ***") else {
+            return;
+        };
+        let tasks = tasks.split("\n***").next().unwrap_or_default();
+        processes.clear();
+        tasks.split('\n')
+             .filter_map(|line| self.data_re.captures(line).map(|data_caps| (data_caps, line)))
+             .for_each(|(data_caps, line)| {
+                 // "Energy Impact" is the last column of the table
+                 let energy_impact = line.split_whitespace()
+                                         .next_back()
+                                         .and_then(|field| field.parse::<f64>().ok())
+                                         .unwrap_or_default();
+                 processes.push(ProcessMetrics { pid: data_caps[2].parse().unwrap_or_default(),
+                                                 name: data_caps[1].to_string(),
+                                                 cpu_ms_per_s: data_caps[3].parse()
+                                                                           .unwrap_or_default(),
+                                                 energy_impact });
+             })
     }
 }
 
-impl Default for Metrics {
+impl Default for Collector {
     fn default() -> Self {
         Self::new()
     }
 }
 
-fn get_soc_info() -> HashMap<String, String> {
-    let cpu_info = get_cpu_info();
-    let core_count = get_core_count();
-
-    let mut res = HashMap::new();
-    if let Some(val) = core_count.get("hw.perflevel1.logicalcpu") {
-        res.insert("e_core_count".to_string(), val.to_owned());
-    }
-    if let Some(val) = core_count.get("hw.perflevel0.logicalcpu") {
-        res.insert("p_core_count".to_string(), val.to_owned());
+impl Default for Parser {
+    fn default() -> Self {
+        Self::new()
     }
-    res.insert("name".to_string(), cpu_info["machdep.cpu.brand_string"].to_owned());
-    res.insert("core_count".to_string(), cpu_info["machdep.cpu.core_count"].to_owned());
-    res.insert("gpu_core_count".to_string(), get_gpu_cores());
-
-    res
 }
 
-fn get_cpu_info() -> HashMap<String, String> {
-    let mut res = HashMap::new();
-    let mut child =
-        Command::new("sysctl").args(["machdep.cpu"]).stdout(Stdio::piped()).spawn().unwrap();
-    if let Some(stdout) = child.stdout.take() {
-        let mut reader = BufReader::new(stdout);
-        let mut info = String::new();
-        reader.read_to_string(&mut info).unwrap();
-
-        info.split('\n').for_each(|line| {
-                            if line.contains("machdep.cpu.brand_string") {
-                                if let Some((_, brand_string)) = line.split_once(':') {
-                                    res.insert("machdep.cpu.brand_string".to_string(),
-                                               brand_string.trim().to_string());
-                                }
-                            } else if line.contains("machdep.cpu.core_count") {
-                                if let Some((_, brand_string)) = line.split_once(':') {
-                                    res.insert("machdep.cpu.core_count".to_string(),
-                                               brand_string.trim().to_string());
-                                }
-                            }
-                        })
-    }
-
-    res
+/// A finite number, `None` for anything else.
+fn number(text: &str) -> Option<f64> {
+    text.trim().parse::<f64>().ok().filter(|value| value.is_finite())
 }
 
-fn get_core_count() -> HashMap<String, String> {
-    let mut res = HashMap::new();
-    let mut child = Command::new("sysctl").args(["hw.perflevel0.logicalcpu",
-                                                 "hw.perflevel1.logicalcpu"])
-                                          .stdout(Stdio::piped())
-                                          .spawn()
-                                          .unwrap();
-    if let Some(stdout) = child.stdout.take() {
-        let mut reader = BufReader::new(stdout);
-        let mut info = String::new();
-        reader.read_to_string(&mut info).unwrap();
-
-        info.split('\n').for_each(|line| {
-                            if line.contains("hw.perflevel0.logicalcpu") {
-                                if let Some((_, brand_string)) = line.split_once(':') {
-                                    res.insert("hw.perflevel0.logicalcpu".to_string(),
-                                               brand_string.trim().to_string());
-                                }
-                            } else if line.contains("hw.perflevel1.logicalcpu") {
-                                if let Some((_, brand_string)) = line.split_once(':') {
-                                    res.insert("hw.perflevel1.logicalcpu".to_string(),
-                                               brand_string.trim().to_string());
-                                }
-                            }
-                        })
-    }
-
-    res
+/// Name and value in watts of lines like `CPU Power: 1234 mW` or `... package power: 3.04W`.
+fn power(line: &str) -> Option<(&str, f64)> {
+    let (name, value) = line.split_once(':')?;
+    let value = value.trim();
+    let watts = match value.strip_suffix("mW") {
+        Some(milliwatts) => number(milliwatts)? / 1000.0,
+        None => number(value.strip_suffix('W')?)?,
+    };
+    Some((name.trim(), watts))
 }
 
-fn get_gpu_cores() -> String {
-    let mut child =
-        Command::new("system_profiler").args(["-detailLevel", "basic", "SPDisplaysDataType"])
-                                       .stdout(Stdio::piped())
-                                       .spawn()
-                                       .unwrap();
-    if let Some(stdout) = child.stdout.take() {
-        let mut reader = BufReader::new(stdout);
-        let mut info = String::new();
-        reader.read_to_string(&mut info).unwrap();
-
-        for line in info.lines() {
-            if line.contains("Total Number of Cores") {
-                let parts: Vec<&str> = line.split(':').collect();
-                if let Some(cores) = parts.get(1) {
-                    return cores.to_string();
-                }
-            }
-        }
-    }
+/// The entry of the cluster `name` in `clusters`, added if missing.
+fn cluster<'a>(clusters: &'a mut Vec<(String, i64, i64)>,
+               name: &str)
+               -> &'a mut (String, i64, i64) {
+    let index = match clusters.iter().position(|(cluster, ..)| cluster == name) {
+        Some(index) => index,
+        None => {
+            clusters.push((name.to_string(), 0, 0));
+            clusters.len() - 1
+        },
+    };
+    &mut clusters[index]
+}
 
-    "?".to_string()
+/// Seconds since the Unix epoch.
+pub fn now() -> f64 {
+    SystemTime::now().duration_since(UNIX_EPOCH).map(|d| d.as_secs_f64()).unwrap_or_default()
 }
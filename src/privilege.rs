@@ -0,0 +1,124 @@
+use std::{
+    env,
+    io::{self, BufRead, IsTerminal, Write},
+    os::unix::process::CommandExt,
+    path::{Path, PathBuf},
+    process::{Child, Command, Stdio},
+};
+
+use serde::Deserialize;
+
+use crate::{app::AppResult, config::Config, metrics::is_root};
+
+/// `[privileges]` section of the config.
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct PrivilegesConfig {
+    /// What to do when started without root.
+    pub escalate: Escalate,
+}
+
+/// How to get the root `powermetrics` needs.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum Escalate {
+    /// Ask on the terminal before the interface starts.
+    #[default]
+    Ask,
+    /// Run only the collector as root, with `sudo mactop-rs agent --stdio`.
+    Helper,
+    /// Restart the whole of mactop-rs with `sudo`.
+    Sudo,
+    /// Run without root, and without the metrics of `powermetrics`.
+    Never,
+}
+
+/// Decides how to get root before the interface starts, asking if configured to.
+///
+/// Never returns [`Escalate::Ask`]. Returns [`Escalate::Never`] when already root, when
+/// `powermetrics` isn't installed, or when there is no terminal to ask on.
+pub fn choose(config: &PrivilegesConfig) -> Escalate {
+    if is_root() || find_in_path("powermetrics").is_none() {
+        return Escalate::Never;
+    }
+    match config.escalate {
+        Escalate::Ask if io::stdin().is_terminal() && io::stderr().is_terminal() => {
+            ask().unwrap_or(Escalate::Never)
+        },
+        Escalate::Ask => Escalate::Never,
+        escalate => escalate,
+    }
+}
+
+/// Asks on the terminal, before raw mode, so the answer and a password prompt can be typed.
+fn ask() -> io::Result<Escalate> {
+    let mut stderr = io::stderr();
+    write!(
+        stderr,
+        "mactop-rs reads power, frequencies and processes from powermetrics, which needs root.\n  \
+         h  run only the collector as root, with sudo (default)\n  \
+         s  run all of mactop-rs with sudo\n  \
+         n  continue without root\n\
+         Set `escalate` in [privileges] of the config to stop asking.\n\
+         Choice [h/s/n]: "
+    )?;
+    stderr.flush()?;
+    let mut answer = String::new();
+    if io::stdin().lock().read_line(&mut answer)? == 0 {
+        return Ok(Escalate::Never);
+    }
+    Ok(match answer.trim().to_lowercase().as_str() {
+        "" | "h" | "helper" => Escalate::Helper,
+        "s" | "sudo" => Escalate::Sudo,
+        _ => Escalate::Never,
+    })
+}
+
+/// Replaces this process with the same command run through `sudo`.
+///
+/// Root has its own home, so the config is passed along unless given already. Only returns if
+/// `sudo` couldn't be started.
+pub fn reexec_with_sudo(config: Option<&Path>) -> AppResult<()> {
+    let exe = env::current_exe()?;
+    let mut command = Command::new("sudo");
+    command.arg("--").arg(exe).args(env::args_os().skip(1));
+    if config.is_none() {
+        if let Some(path) = Config::default_path().filter(|path| path.exists()) {
+            command.arg("--config").arg(path);
+        }
+    }
+    let err = command.exec();
+    Err(format!("failed to run sudo: {err}").into())
+}
+
+/// Asks `sudo` for the password now, on the normal terminal, so the helper started later by
+/// [`spawn_helper`] doesn't need to.
+pub fn authenticate() -> AppResult<()> {
+    let status = Command::new("sudo").arg("-v").status()?;
+    if !status.success() {
+        return Err(format!("sudo failed: {status}").into());
+    }
+    Ok(())
+}
+
+/// Starts `mactop-rs agent --stdio` as root, writing a snapshot per line on its stdout.
+///
+/// Only this helper runs as root, the interface keeps the user's privileges and files. Its stderr
+/// is piped, to tell why it stopped, e.g. `sudo` asking for a password again.
+pub fn spawn_helper() -> io::Result<Child> {
+    Command::new("sudo")
+        .arg("-n")
+        .arg("--")
+        .arg(env::current_exe()?)
+        .args(["agent", "--stdio"])
+        .stdin(Stdio::null())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+}
+
+/// The file `name` in a directory of the `PATH`.
+fn find_in_path(name: &str) -> Option<PathBuf> {
+    let path = env::var_os("PATH")?;
+    env::split_paths(&path).map(|dir| dir.join(name)).find(|file| file.is_file())
+}
@@ -0,0 +1,205 @@
+use std::{
+    collections::VecDeque,
+    io::{self, BufRead, BufReader, Read, Write},
+    net::{TcpListener, TcpStream, ToSocketAddrs},
+    sync::{mpsc, Arc, Condvar, Mutex},
+    thread,
+    time::Duration,
+};
+
+use crate::{
+    app::AppResult,
+    event::Event,
+    metrics::{is_root, Collector, Metrics},
+};
+
+/// Longest pause between two reconnection attempts.
+const MAX_BACKOFF: Duration = Duration::from_secs(10);
+
+/// Snapshots waiting for a slow client, older ones are dropped.
+const MAX_QUEUED: usize = 4;
+
+/// Time a write to a client may block before the client is dropped.
+const WRITE_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// Time a connection to an agent may take to open.
+const CONNECT_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Time without a snapshot after which the agent is taken for gone, e.g. asleep or off the
+/// network without closing the connection: a few sample intervals.
+pub const READ_TIMEOUT: Duration = Duration::from_millis(3 * 2500);
+
+/// State of the connection to a remote agent.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub enum ConnectionState {
+    /// Waiting for the first connection.
+    #[default]
+    Connecting,
+    /// Snapshots are being received.
+    Connected,
+    /// The connection failed or dropped, a new attempt is scheduled.
+    Lost(String),
+}
+
+/// Runs the agent: collects metrics forever and writes every snapshot as a JSON line to each
+/// connected client.
+pub fn serve(addr: &str) -> AppResult<()> {
+    let listener = TcpListener::bind(addr)?;
+    let local_addr = listener.local_addr()?;
+    eprintln!("mactop-rs agent listening on {local_addr}");
+    if !local_addr.ip().is_loopback() {
+        eprintln!("reachable from other machines without authentication, processes included");
+    }
+    if !is_root() {
+        eprintln!("not running as root, powermetrics won't run: start the agent with sudo");
+    }
+    serve_on(listener)
+}
+
+/// Runs the agent on a bound `listener`, see [`serve`].
+pub fn serve_on(listener: TcpListener) -> AppResult<()> {
+    let clients: Arc<Mutex<Vec<Arc<Outbox>>>> = Arc::default();
+    let accepted = Arc::clone(&clients);
+    thread::spawn(move || {
+        for stream in listener.incoming().flatten() {
+            if let Ok(peer) = stream.peer_addr() {
+                eprintln!("client connected from {peer}");
+            }
+            let _ = stream.set_nodelay(true);
+            let _ = stream.set_write_timeout(Some(WRITE_TIMEOUT));
+            let outbox = Arc::new(Outbox::default());
+            let writer = Arc::clone(&outbox);
+            thread::spawn(move || writer.send_all(stream));
+            accepted.lock().unwrap().push(outbox);
+        }
+    });
+
+    let mut collector = Collector::new();
+    loop {
+        let metrics = collector.collect();
+        let mut line = serde_json::to_string(&metrics)?;
+        line.push('\n');
+        let line: Arc<str> = line.into();
+        // Only queues, so a stalled client never holds up sampling nor the others.
+        clients.lock().unwrap().retain(|outbox| outbox.push(Arc::clone(&line)));
+    }
+}
+
+/// Snapshots queued for one client, written by its own thread.
+#[derive(Debug, Default)]
+struct Outbox {
+    /// Lines not written yet, and whether the client is gone.
+    queue: Mutex<(VecDeque<Arc<str>>, bool)>,
+    ready: Condvar,
+}
+
+impl Outbox {
+    /// Queues `line`, dropping the oldest one when the client lags. Returns `false` once the
+    /// client is gone.
+    fn push(&self, line: Arc<str>) -> bool {
+        let mut queue = self.queue.lock().unwrap();
+        if queue.1 {
+            return false;
+        }
+        if queue.0.len() == MAX_QUEUED {
+            queue.0.pop_front();
+        }
+        queue.0.push_back(line);
+        self.ready.notify_one();
+        true
+    }
+
+    /// Writes queued lines to `stream` until a write fails or times out.
+    fn send_all(&self, mut stream: TcpStream) {
+        loop {
+            let line = {
+                let mut queue = self.queue.lock().unwrap();
+                loop {
+                    match queue.0.pop_front() {
+                        Some(line) => break line,
+                        None => queue = self.ready.wait(queue).unwrap(),
+                    }
+                }
+            };
+            if stream.write_all(line.as_bytes()).is_err() {
+                self.queue.lock().unwrap().1 = true;
+                return;
+            }
+        }
+    }
+}
+
+/// Runs the root helper: collects metrics and writes every snapshot as a JSON line to stdout,
+/// until the interface reading it goes away.
+pub fn serve_stdio() -> AppResult<()> {
+    let mut stdout = io::stdout().lock();
+    let mut collector = Collector::new();
+    loop {
+        let metrics = collector.collect();
+        let mut line = serde_json::to_string(&metrics)?;
+        line.push('\n');
+        if stdout.write_all(line.as_bytes()).and_then(|()| stdout.flush()).is_err() {
+            return Ok(());
+        }
+    }
+}
+
+/// Spawns a thread that keeps a connection to the agent at `addr` and forwards its snapshots as
+/// [`Event::Metrics`] tagged with `index`, reconnecting with a growing backoff whenever the
+/// connection drops or no snapshot comes for [`READ_TIMEOUT`].
+pub fn spawn_client(index: usize, addr: String, sender: mpsc::Sender<Event>) {
+    thread::spawn(move || {
+        let mut backoff = Duration::from_secs(1);
+        loop {
+            let state = match connect(&addr) {
+                Ok(stream) => {
+                    if sender.send(Event::Connection(index, ConnectionState::Connected)).is_err() {
+                        return;
+                    }
+                    backoff = Duration::from_secs(1);
+                    match receive(index, stream, &sender) {
+                        Ok(()) => ConnectionState::Lost("connection closed by agent".to_string()),
+                        Err(err) => match err.downcast_ref::<io::Error>().map(io::Error::kind) {
+                            Some(io::ErrorKind::WouldBlock | io::ErrorKind::TimedOut) => {
+                                ConnectionState::Lost(format!("no snapshot for {}s",
+                                                              READ_TIMEOUT.as_secs_f64()))
+                            },
+                            _ => ConnectionState::Lost(err.to_string()),
+                        },
+                    }
+                },
+                Err(err) => ConnectionState::Lost(err.to_string()),
+            };
+            if sender.send(Event::Connection(index, state)).is_err() {
+                return;
+            }
+            thread::sleep(backoff);
+            backoff = (backoff * 2).min(MAX_BACKOFF);
+        }
+    });
+}
+
+/// Connects to the first address `addr` resolves to that accepts, with reads timing out after
+/// [`READ_TIMEOUT`].
+fn connect(addr: &str) -> io::Result<TcpStream> {
+    let mut last_err = io::Error::new(io::ErrorKind::InvalidInput, "no address to connect to");
+    for socket_addr in addr.to_socket_addrs()? {
+        match TcpStream::connect_timeout(&socket_addr, CONNECT_TIMEOUT) {
+            Ok(stream) => {
+                stream.set_read_timeout(Some(READ_TIMEOUT))?;
+                return Ok(stream);
+            },
+            Err(err) => last_err = err,
+        }
+    }
+    Err(last_err)
+}
+
+/// Forwards snapshots from `stream` until it is closed.
+pub fn receive(index: usize, stream: impl Read, sender: &mpsc::Sender<Event>) -> AppResult<()> {
+    for line in BufReader::new(stream).lines() {
+        let metrics: Metrics = serde_json::from_str(&line?)?;
+        sender.send(Event::Metrics(index, Box::new(metrics)))?;
+    }
+    Ok(())
+}
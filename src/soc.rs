@@ -0,0 +1,194 @@
+use std::{
+    collections::HashMap,
+    fs,
+    path::Path,
+    process::{Command, Stdio},
+};
+
+use serde::{Deserialize, Serialize};
+
+/// What the machine is made of, read once at startup.
+///
+/// Counts are 0 when unknown.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct SocInfo {
+    /// Chip or CPU name, e.g. `Apple M1 Pro`, empty if unknown.
+    pub model: String,
+    /// Number of CPU cores.
+    pub core_count: u32,
+    /// Number of efficiency cores, numbered before the performance cores.
+    pub e_core_count: u32,
+    /// Number of performance cores, all cores on CPUs with a single kind of core.
+    pub p_core_count: u32,
+    /// Number of GPU cores, only known on macOS.
+    pub gpu_core_count: Option<u32>,
+    /// Physical memory in bytes.
+    pub memory_bytes: u64,
+    /// OS name and version, e.g. `macOS 14.4`, empty if unknown.
+    pub os_version: String,
+}
+
+impl SocInfo {
+    /// Reads the info of this machine.
+    pub fn detect() -> Self {
+        if cfg!(target_os = "macos") {
+            Self::from_macos()
+        } else {
+            Self::from_linux(Path::new("/"))
+        }
+    }
+
+    /// Reads the info from `sysctl`, `sw_vers` and `system_profiler`.
+    pub fn from_macos() -> Self {
+        // Keys missing on a machine, e.g. perflevels on Intel, are left out of the output.
+        let sysctl = run(
+            "sysctl",
+            &[
+                "machdep.cpu.brand_string",
+                "machdep.cpu.core_count",
+                "hw.perflevel0.logicalcpu",
+                "hw.perflevel1.logicalcpu",
+                "hw.memsize",
+            ],
+        );
+        let sysctl = fields(&sysctl);
+        let number = |key: &str| sysctl.get(key).and_then(|value| value.parse::<u64>().ok());
+
+        let mut info = SocInfo {
+            model: sysctl.get("machdep.cpu.brand_string").cloned().unwrap_or_default(),
+            e_core_count: number("hw.perflevel1.logicalcpu").unwrap_or_default() as u32,
+            p_core_count: number("hw.perflevel0.logicalcpu").unwrap_or_default() as u32,
+            memory_bytes: number("hw.memsize").unwrap_or_default(),
+            ..Default::default()
+        };
+        info.core_count = number("machdep.cpu.core_count")
+            .map(|count| count as u32)
+            .unwrap_or(info.e_core_count + info.p_core_count);
+        if info.p_core_count == 0 {
+            info.p_core_count = info.core_count;
+        }
+        let version = run("sw_vers", &["-productVersion"]);
+        if !version.trim().is_empty() {
+            info.os_version = format!("macOS {}", version.trim());
+        }
+        info.gpu_core_count =
+            fields(&run("system_profiler", &["-detailLevel", "basic", "SPDisplaysDataType"]))
+                .get("Total Number of Cores")
+                .and_then(|cores| cores.parse().ok());
+        info
+    }
+
+    /// Reads the info from `/proc` and `/sys` under `root`, `/` but for tests.
+    ///
+    /// Efficiency cores are told apart by their capacity, or their maximum frequency without it:
+    /// cores at least 80% as fast as the fastest one are performance cores.
+    pub fn from_linux(root: &Path) -> Self {
+        let read = |path: &str| fs::read_to_string(root.join(path)).unwrap_or_default();
+        let cpuinfo = read("proc/cpuinfo");
+        let cpuinfo = fields(&cpuinfo);
+        // Asahi Linux and other device tree machines only name the board, e.g.
+        // `Apple MacBook Air (M1, 2020)`.
+        let device_tree = read("proc/device-tree/model");
+        let device_tree = device_tree.trim_end_matches('\0').trim();
+        let model = cpuinfo
+            .get("model name")
+            .or(cpuinfo.get("Model"))
+            .map(String::as_str)
+            .or(Some(device_tree).filter(|model| !model.is_empty()))
+            .or(cpuinfo.get("Hardware").map(String::as_str))
+            .unwrap_or_default()
+            .to_string();
+
+        let cpu_dir = root.join("sys/devices/system/cpu");
+        let mut cpus = cpu_list(&read("sys/devices/system/cpu/online"));
+        if cpus.is_empty() {
+            cpus = fs::read_dir(&cpu_dir)
+                .into_iter()
+                .flatten()
+                .flatten()
+                .filter_map(|entry| entry.file_name().to_str()?.strip_prefix("cpu")?.parse().ok())
+                .collect();
+            cpus.sort_unstable();
+        }
+        let core_count = if cpus.is_empty() {
+            read("proc/cpuinfo").lines().filter(|line| line.starts_with("processor")).count()
+        } else {
+            cpus.len()
+        } as u32;
+
+        let speeds: Vec<u64> = cpus
+            .iter()
+            .filter_map(|cpu| {
+                ["cpu_capacity", "cpufreq/cpuinfo_max_freq"].iter().find_map(|file| {
+                    let path = cpu_dir.join(format!("cpu{cpu}/{file}"));
+                    fs::read_to_string(path).ok()?.trim().parse().ok()
+                })
+            })
+            .collect();
+        let fastest = speeds.iter().copied().max().unwrap_or_default();
+        let e_core_count = if speeds.len() == cpus.len() {
+            speeds.iter().filter(|&&speed| speed * 5 < fastest * 4).count() as u32
+        } else {
+            0
+        };
+
+        let memory_kb: u64 = fields(&read("proc/meminfo"))
+            .get("MemTotal")
+            .and_then(|total| total.trim_end_matches("kB").trim().parse().ok())
+            .unwrap_or_default();
+        let os_release = read("etc/os-release");
+        let os_version = os_release
+            .lines()
+            .find_map(|line| line.strip_prefix("PRETTY_NAME="))
+            .map(|name| name.trim_matches('"').to_string())
+            .or_else(|| {
+                let kernel = read("proc/sys/kernel/osrelease");
+                Some(format!("Linux {}", kernel.trim())).filter(|_| !kernel.trim().is_empty())
+            })
+            .unwrap_or_default();
+
+        SocInfo {
+            model,
+            core_count,
+            e_core_count,
+            p_core_count: core_count - e_core_count,
+            gpu_core_count: None,
+            memory_bytes: memory_kb * 1024,
+            os_version,
+        }
+    }
+}
+
+/// Output of a command, empty if it couldn't run.
+fn run(program: &str, args: &[&str]) -> String {
+    Command::new(program)
+        .args(args)
+        .stderr(Stdio::null())
+        .output()
+        .map(|output| String::from_utf8_lossy(&output.stdout).into_owned())
+        .unwrap_or_default()
+}
+
+/// `key: value` lines, the first value of every key.
+fn fields(text: &str) -> HashMap<String, String> {
+    let mut fields = HashMap::new();
+    for line in text.lines() {
+        if let Some((key, value)) = line.split_once(':') {
+            fields.entry(key.trim().to_string()).or_insert_with(|| value.trim().to_string());
+        }
+    }
+    fields
+}
+
+/// CPU numbers of a list like `0-3,6`.
+fn cpu_list(list: &str) -> Vec<u32> {
+    list.trim()
+        .split(',')
+        .filter_map(|range| match range.split_once('-') {
+            Some((start, end)) => Some(start.parse().ok()?..=end.parse().ok()?),
+            None => range.parse().ok().map(|cpu| cpu..=cpu),
+        })
+        .flatten()
+        .collect()
+}
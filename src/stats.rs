@@ -0,0 +1,169 @@
+use serde::Deserialize;
+
+use crate::history::Series;
+
+/// Time span that statistics are computed over.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum StatsWindow {
+    #[default]
+    Seconds30,
+    Minutes5,
+    Hour1,
+}
+
+impl StatsWindow {
+    /// Length of the window in seconds.
+    pub fn seconds(self) -> f64 {
+        match self {
+            StatsWindow::Seconds30 => 30.0,
+            StatsWindow::Minutes5 => 300.0,
+            StatsWindow::Hour1 => 3600.0,
+        }
+    }
+
+    /// Short name, as in the config.
+    pub fn label(self) -> &'static str {
+        match self {
+            StatsWindow::Seconds30 => "30s",
+            StatsWindow::Minutes5 => "5m",
+            StatsWindow::Hour1 => "1h",
+        }
+    }
+
+    /// The next longer window, wrapping around to the shortest.
+    pub fn next(self) -> Self {
+        match self {
+            StatsWindow::Seconds30 => StatsWindow::Minutes5,
+            StatsWindow::Minutes5 => StatsWindow::Hour1,
+            StatsWindow::Hour1 => StatsWindow::Seconds30,
+        }
+    }
+}
+
+/// Time span shown by the history charts.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+pub enum ChartWindow {
+    #[default]
+    #[serde(rename = "1m")]
+    Minute1,
+    #[serde(rename = "5m")]
+    Minutes5,
+    #[serde(rename = "15m")]
+    Minutes15,
+    #[serde(rename = "1h")]
+    Hour1,
+}
+
+impl ChartWindow {
+    const ALL: [ChartWindow; 4] =
+        [ChartWindow::Minute1, ChartWindow::Minutes5, ChartWindow::Minutes15, ChartWindow::Hour1];
+
+    /// Length of the window in seconds.
+    pub fn seconds(self) -> f64 {
+        match self {
+            ChartWindow::Minute1 => 60.0,
+            ChartWindow::Minutes5 => 300.0,
+            ChartWindow::Minutes15 => 900.0,
+            ChartWindow::Hour1 => 3600.0,
+        }
+    }
+
+    pub fn label(self) -> &'static str {
+        match self {
+            ChartWindow::Minute1 => "1m",
+            ChartWindow::Minutes5 => "5m",
+            ChartWindow::Minutes15 => "15m",
+            ChartWindow::Hour1 => "1h",
+        }
+    }
+
+    /// The next shorter window, staying at the shortest.
+    pub fn zoom_in(self) -> Self {
+        let index = ChartWindow::ALL.iter().position(|&window| window == self).unwrap_or_default();
+        ChartWindow::ALL[index.saturating_sub(1)]
+    }
+
+    /// The next longer window, staying at the longest.
+    pub fn zoom_out(self) -> Self {
+        let index = ChartWindow::ALL.iter().position(|&window| window == self).unwrap_or_default();
+        ChartWindow::ALL[(index + 1).min(ChartWindow::ALL.len() - 1)]
+    }
+}
+
+/// Summary of the samples in a slice of time, so long windows fit the width of a chart.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Bucket {
+    /// Middle of the slice.
+    pub time: f64,
+    pub min: f64,
+    pub mean: f64,
+    pub max: f64,
+}
+
+/// Summary of the values in a window.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Stats {
+    pub min: f64,
+    pub mean: f64,
+    pub max: f64,
+    /// 95th percentile, nearest-rank.
+    pub p95: f64,
+    /// Number of values.
+    pub count: usize,
+}
+
+impl Stats {
+    /// Computes the statistics of `values`, `None` if there are none.
+    pub fn of(values: impl IntoIterator<Item = f64>) -> Option<Self> {
+        let mut values: Vec<f64> = values.into_iter().filter(|value| value.is_finite()).collect();
+        if values.is_empty() {
+            return None;
+        }
+        values.sort_by(f64::total_cmp);
+        let count = values.len();
+        let rank = ((count as f64 * 0.95).ceil() as usize).clamp(1, count);
+        Some(Self {
+            min: values[0],
+            mean: values.iter().sum::<f64>() / count as f64,
+            max: values[count - 1],
+            p95: values[rank - 1],
+            count,
+        })
+    }
+}
+
+impl Series {
+    /// Statistics of the samples in the `window` ending at `end`.
+    pub fn stats(&self, window: StatsWindow, end: f64) -> Option<Stats> {
+        let start = end - window.seconds();
+        Stats::of(self.until(end).filter(|s| s.time >= start).map(|sample| sample.value))
+    }
+
+    /// Splits the `seconds` ending at `end` into `count` equal slices and summarizes the samples
+    /// of each, skipping empty slices.
+    pub fn downsample(&self, seconds: f64, end: f64, count: usize) -> Vec<Bucket> {
+        let start = end - seconds;
+        let width = seconds / count.max(1) as f64;
+        let mut buckets: Vec<(usize, Bucket, usize)> = vec![];
+        for sample in self.until(end).filter(|s| s.time >= start && s.value.is_finite()) {
+            let index = (((sample.time - start) / width) as usize).min(count.max(1) - 1);
+            match buckets.last_mut() {
+                Some((last, bucket, samples)) if *last == index => {
+                    bucket.min = bucket.min.min(sample.value);
+                    bucket.max = bucket.max.max(sample.value);
+                    bucket.mean += sample.value;
+                    *samples += 1;
+                },
+                _ => {
+                    let time = start + (index as f64 + 0.5) * width;
+                    let value = sample.value;
+                    buckets.push((index, Bucket { time, min: value, mean: value, max: value }, 1));
+                },
+            }
+        }
+        buckets
+            .into_iter()
+            .map(|(_, bucket, samples)| Bucket { mean: bucket.mean / samples as f64, ..bucket })
+            .collect()
+    }
+}
@@ -0,0 +1,144 @@
+use std::{
+    fs::{self, File, OpenOptions},
+    io::{BufRead, BufReader, BufWriter, Write},
+    path::{Path, PathBuf},
+    time::{Duration, SystemTime},
+};
+
+use crate::{
+    app::AppResult,
+    config::StoreConfig,
+    metrics::{now, Metrics},
+};
+
+/// Processes kept per stored snapshot, by energy impact. The full task list would make every
+/// line several kilobytes.
+const STORED_PROCESSES: usize = 32;
+
+/// Append-only store of snapshots as JSON lines, split into segments named after the time of
+/// their first snapshot.
+#[derive(Debug)]
+pub struct Store {
+    dir: PathBuf,
+    config: StoreConfig,
+    /// Open segment, its start time and size.
+    segment: Option<(BufWriter<File>, f64, u64)>,
+}
+
+impl Store {
+    /// Opens the store of the host called `name` below the configured directory.
+    pub fn open(config: &StoreConfig, name: &str) -> AppResult<Self> {
+        let dir = config.dir().ok_or("no directory for the history store")?.join(slug(name));
+        fs::create_dir_all(&dir)
+            .map_err(|err| format!("failed to create {}: {err}", dir.display()))?;
+        Ok(Self { dir, config: config.clone(), segment: None })
+    }
+
+    /// Directory holding the segments.
+    pub fn dir(&self) -> &Path {
+        &self.dir
+    }
+
+    /// Appends `metrics`, starting a new segment and pruning old ones when the current segment is
+    /// full.
+    pub fn append(&mut self, metrics: &Metrics) -> AppResult<()> {
+        let full = self.segment.as_ref().is_some_and(|&(_, start, size)| {
+            size >= self.config.segment_bytes
+                || metrics.timestamp - start >= self.config.segment_minutes * 60.0
+        });
+        if full {
+            if let Some((mut writer, ..)) = self.segment.take() {
+                writer.flush()?;
+            }
+            self.prune()?;
+        }
+        if self.segment.is_none() {
+            let path = self.dir.join(format!("segment-{}.jsonl", metrics.timestamp as u64));
+            let file = OpenOptions::new().create(true).append(true).open(path)?;
+            let size = file.metadata()?.len();
+            self.segment = Some((BufWriter::new(file), metrics.timestamp, size));
+        }
+
+        let mut metrics = metrics.clone();
+        metrics.processes.sort_by(|a, b| b.energy_impact.total_cmp(&a.energy_impact));
+        metrics.processes.truncate(STORED_PROCESSES);
+        let mut line = serde_json::to_string(&metrics)?;
+        line.push('\n');
+        if let Some((writer, _, size)) = &mut self.segment {
+            writer.write_all(line.as_bytes())?;
+            writer.flush()?;
+            *size += line.len() as u64;
+        }
+        Ok(())
+    }
+
+    /// Snapshots taken at or after `since`, oldest first. Unreadable lines are skipped, a crash
+    /// may have cut the last one short.
+    pub fn load_since(&self, since: f64) -> AppResult<Vec<Metrics>> {
+        let mut snapshots = vec![];
+        for (path, _, modified) in self.segments()? {
+            if modified < since {
+                continue;
+            }
+            for line in BufReader::new(File::open(path)?).lines() {
+                if let Ok(metrics) = serde_json::from_str::<Metrics>(&line?) {
+                    if metrics.timestamp >= since {
+                        snapshots.push(metrics);
+                    }
+                }
+            }
+        }
+        snapshots.sort_by(|a, b| a.timestamp.total_cmp(&b.timestamp));
+        Ok(snapshots)
+    }
+
+    /// Deletes segments older than the maximum age, then the oldest ones until the store fits the
+    /// maximum size. The open segment is never deleted.
+    pub fn prune(&self) -> AppResult<()> {
+        let oldest = now() - self.config.max_age_hours * 3600.0;
+        let mut segments = self.segments()?;
+        let open = self.segment.as_ref().map(|&(_, start, _)| start as u64);
+        let mut total: u64 = segments.iter().map(|(_, size, _)| size).sum();
+        segments.retain(|(path, _, _)| open.is_none() || start_of(path) != open);
+        for (path, size, modified) in segments {
+            if modified < oldest || total > self.config.max_bytes {
+                fs::remove_file(&path)?;
+                total = total.saturating_sub(size);
+            }
+        }
+        Ok(())
+    }
+
+    /// Every segment with its size and modification time, oldest first.
+    fn segments(&self) -> AppResult<Vec<(PathBuf, u64, f64)>> {
+        let mut segments = vec![];
+        for entry in fs::read_dir(&self.dir)? {
+            let path = entry?.path();
+            if start_of(&path).is_none() {
+                continue;
+            }
+            let metadata = fs::metadata(&path)?;
+            let modified = metadata
+                .modified()?
+                .duration_since(SystemTime::UNIX_EPOCH)
+                .unwrap_or(Duration::ZERO)
+                .as_secs_f64();
+            segments.push((path, metadata.len(), modified));
+        }
+        segments.sort_by_key(|(path, ..)| start_of(path));
+        Ok(segments)
+    }
+}
+
+/// Start time of the segment at `path`, `None` if it is not a segment.
+fn start_of(path: &Path) -> Option<u64> {
+    let name = path.file_name()?.to_str()?;
+    name.strip_prefix("segment-")?.strip_suffix(".jsonl")?.parse().ok()
+}
+
+/// A directory name for the host called `name`.
+fn slug(name: &str) -> String {
+    name.chars()
+        .map(|c| if c.is_ascii_alphanumeric() || c == '.' || c == '-' { c } else { '_' })
+        .collect()
+}
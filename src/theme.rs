@@ -0,0 +1,163 @@
+use std::{collections::HashMap, env};
+
+use ratatui::style::{Color, Modifier, Style};
+use serde::Deserialize;
+
+use crate::layout::Panel;
+
+/// Built-in set of colors.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum Palette {
+    /// The original colors, on black gauges.
+    #[default]
+    Dark,
+    /// Darker colors without black backgrounds, for light terminals.
+    Light,
+    /// Bright colors and bold text.
+    HighContrast,
+    /// Text attributes only, used when `NO_COLOR` is set.
+    Monochrome,
+}
+
+/// Colors of one panel, replacing those of the palette.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct PanelColors {
+    /// A name (`"blue"`, `"light-green"`), an index (`"208"`) or `"#rrggbb"`.
+    pub fg: Option<Color>,
+    pub bg: Option<Color>,
+}
+
+/// `[theme]` section of the config.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct ThemeConfig {
+    pub palette: Palette,
+    /// Per-panel overrides, e.g. `[theme.panels.e-cpu]`.
+    pub panels: HashMap<Panel, PanelColors>,
+}
+
+/// Styles used by the renderer.
+#[derive(Debug, Clone)]
+pub struct Theme {
+    /// Host names.
+    pub accent: Style,
+    /// Key hints.
+    pub muted: Style,
+    /// Connected hosts, idle usage, live display.
+    pub good: Style,
+    /// Reconnecting hosts, busy usage, paused display.
+    pub warn: Style,
+    /// Lost hosts, saturated usage.
+    pub bad: Style,
+    /// Lines of charts overlaying several series, in order.
+    pub series: Vec<Style>,
+    /// Gauge, chart or text style of every panel.
+    panels: HashMap<Panel, Style>,
+}
+
+impl Default for Theme {
+    fn default() -> Self {
+        Self::new(Palette::default(), &HashMap::new())
+    }
+}
+
+impl Theme {
+    /// Builds the theme of `config`, in monochrome if the `NO_COLOR` environment variable is set.
+    pub fn from_config(config: &ThemeConfig) -> Self {
+        // https://no-color.org: any non-empty value disables colors, overrides included.
+        if env::var_os("NO_COLOR").is_some_and(|value| !value.is_empty()) {
+            return Self::new(Palette::Monochrome, &HashMap::new());
+        }
+        Self::new(config.palette, &config.panels)
+    }
+
+    /// Builds the theme of `palette` with the colors of some panels replaced.
+    pub fn new(palette: Palette, overrides: &HashMap<Panel, PanelColors>) -> Self {
+        let style = |fg: Color| Style::default().fg(fg);
+        let mut theme = match palette {
+            Palette::Dark => Self {
+                accent: style(Color::Cyan),
+                muted: style(Color::DarkGray),
+                good: style(Color::Green),
+                warn: style(Color::Yellow),
+                bad: style(Color::Red),
+                series: [Color::Cyan, Color::Magenta, Color::Green, Color::Yellow]
+                    .map(style)
+                    .into(),
+                panels: HashMap::new(),
+            },
+            Palette::Light => Self {
+                accent: style(Color::Indexed(25)),
+                muted: style(Color::Indexed(244)),
+                good: style(Color::Indexed(28)),
+                warn: style(Color::Indexed(130)),
+                bad: style(Color::Indexed(160)),
+                series: [25, 127, 28, 130].map(|index| style(Color::Indexed(index))).into(),
+                panels: HashMap::new(),
+            },
+            Palette::HighContrast => Self {
+                accent: style(Color::LightCyan).add_modifier(Modifier::BOLD),
+                muted: style(Color::Gray),
+                good: style(Color::LightGreen).add_modifier(Modifier::BOLD),
+                warn: style(Color::LightYellow).add_modifier(Modifier::BOLD),
+                bad: style(Color::LightRed).add_modifier(Modifier::BOLD),
+                series: [
+                    Color::LightCyan,
+                    Color::LightMagenta,
+                    Color::LightGreen,
+                    Color::LightYellow,
+                ]
+                .map(style)
+                .into(),
+                panels: HashMap::new(),
+            },
+            Palette::Monochrome => Self {
+                accent: Style::default().add_modifier(Modifier::BOLD),
+                muted: Style::default().add_modifier(Modifier::DIM),
+                good: Style::default(),
+                warn: Style::default().add_modifier(Modifier::BOLD),
+                bad: Style::default().add_modifier(Modifier::BOLD | Modifier::UNDERLINED),
+                // Charts tell series apart by their markers.
+                series: vec![],
+                panels: HashMap::new(),
+            },
+        };
+        for panel in Panel::ALL {
+            let mut style = palette_panel(palette, panel);
+            if let Some(colors) = overrides.get(&panel) {
+                style.fg = colors.fg.or(style.fg);
+                style.bg = colors.bg.or(style.bg);
+            }
+            theme.panels.insert(panel, style);
+        }
+        theme
+    }
+
+    /// Style of `panel`: the gauge or chart colors, or the text colors of lists and tables.
+    pub fn panel(&self, panel: Panel) -> Style {
+        self.panels.get(&panel).copied().unwrap_or_default()
+    }
+}
+
+/// Default style of `panel` in `palette`.
+fn palette_panel(palette: Palette, panel: Panel) -> Style {
+    let (green, magenta, cyan) = match palette {
+        Palette::Dark => (Color::Green, Color::Magenta, Color::Cyan),
+        Palette::Light => (Color::Indexed(28), Color::Indexed(127), Color::Indexed(25)),
+        Palette::HighContrast => (Color::LightGreen, Color::LightMagenta, Color::LightCyan),
+        Palette::Monochrome => return Style::default(),
+    };
+    let gauge_bg = match palette {
+        Palette::Light => Color::Indexed(254),
+        _ => Color::Black,
+    };
+    match panel {
+        Panel::ECpu | Panel::Memory => Style::default().fg(green).bg(gauge_bg),
+        Panel::PCpu | Panel::Gpu | Panel::Ane => Style::default().fg(magenta).bg(gauge_bg),
+        Panel::PowerChart if palette == Palette::Light => Style::default().fg(cyan),
+        Panel::PowerChart => Style::default().fg(cyan).bg(gauge_bg),
+        _ => Style::default(),
+    }
+}
@@ -5,7 +5,7 @@ use crossterm::event::{DisableMouseCapture, EnableMouseCapture};
 use crossterm::terminal::{self, EnterAlternateScreen, LeaveAlternateScreen};
 use ratatui::backend::Backend;
 use ratatui::Terminal;
-use std::io;
+use std::io::{self, Write};
 use std::panic;
 
 /// Representation of a terminal user interface.
@@ -52,6 +52,12 @@ impl<B: Backend> Tui<B> {
     /// [`rendering`]: crate::ui::render
     pub fn draw(&mut self, app: &mut App) -> AppResult<()> {
         self.terminal.draw(|frame| ui::render(app, frame))?;
+        // Ring the bell for the alerts fired since the last frame.
+        if app.bell {
+            app.bell = false;
+            io::stderr().write_all(b"\x07")?;
+            io::stderr().flush()?;
+        }
         Ok(())
     }
 
@@ -1,13 +1,30 @@
-
 use ratatui::{
-    layout::{Constraint, Direction, Layout},
+    layout::{Alignment, Constraint, Direction, Layout, Rect},
     style::{Style, Stylize},
-    symbols,
-    widgets::{Axis, Block, Chart, Dataset, Gauge, List},
+    text::{Line, Span},
+    widgets::{Block, Cell, Clear, Paragraph, Row, Table, Tabs, Wrap},
     Frame,
 };
 
-use crate::app::App;
+use crate::{
+    alert::Severity,
+    app::App,
+    config::TariffConfig,
+    energy::{format_duration, EnergyMeter},
+    history::{History, MetricKey},
+    keymap::{Action, Keymap},
+    layout::{Panel, Tab},
+    metrics::Degraded,
+    remote::ConnectionState,
+    stats::StatsWindow,
+    theme::Theme,
+};
+
+mod compact;
+mod panels;
+
+use compact::render_compact;
+use panels::{alert_table, energy_table, render_panel, View};
 
 /// Renders the user interface widgets.
 pub fn render(app: &mut App, frame: &mut Frame) {
@@ -16,206 +33,519 @@ pub fn render(app: &mut App, frame: &mut Frame) {
     // - https://docs.rs/ratatui/latest/ratatui/widgets/index.html
     // - https://github.com/ratatui-org/ratatui/tree/master/examples
 
-    let layout = Layout::default()
+    if app.is_dashboard() {
+        app.panels.clear();
+        render_dashboard(app, frame, frame.size());
+        if app.show_help {
+            render_help(&app.keymap, frame, frame.size());
+        }
+        return;
+    }
+
+    app.panels = render_selected(app, frame);
+}
+
+/// Renders the selected host as of the timeline cursor and the popup shown over it, and returns
+/// where its panels were drawn.
+fn render_selected(app: &App, frame: &mut Frame) -> Vec<(Panel, Rect)> {
+    let view = View::new(app);
+    let host = view.host;
+    // Small terminals get one line per metric instead of the tabs.
+    let compact = app.config.compact.applies(frame.size());
+    let header_layout = Layout::default()
         .direction(Direction::Vertical)
         .constraints([
-            Constraint::Percentage(25),
-            Constraint::Percentage(25),
-            Constraint::Percentage(25),
-            Constraint::Percentage(25),
+            Constraint::Length(1),
+            Constraint::Length(if compact { 0 } else { 1 }),
+            Constraint::Min(0),
         ])
         .split(frame.size());
-    let sub_0_layout = Layout::default()
-        .direction(Direction::Horizontal)
-        .constraints([Constraint::Percentage(50), Constraint::Percentage(50)])
-        .split(layout[0]);
-    let sub_1_layout = Layout::default()
-        .direction(Direction::Horizontal)
-        .constraints([Constraint::Percentage(50), Constraint::Percentage(50)])
-        .split(layout[1]);
-    let sub_2_layout = Layout::default()
-        .direction(Direction::Horizontal)
-        .constraints([
-            Constraint::Percentage(25),
-            Constraint::Percentage(25),
-            Constraint::Percentage(25),
-            Constraint::Percentage(25),
+    render_header(&view, frame, header_layout[0]);
+    let area = header_layout[2];
+    let panels = if compact {
+        render_compact(&view, frame, area);
+        vec![]
+    } else {
+        render_tabs(app, frame, header_layout[1]);
+        render_host(&view, frame, area)
+    };
+    if app.show_stats {
+        render_stats_table(&host.history, app.stats_window, view.end, &app.keymap, frame, area);
+    } else if app.show_energy {
+        render_energy_table(&view.energy, &app.config.tariff, &app.keymap, frame, area);
+    } else if app.show_help {
+        render_help(&app.keymap, frame, area);
+    } else if app.show_alerts {
+        render_alert_table(&view, frame, area);
+    } else if let Some(degraded) = host.metrics.degraded.as_ref().filter(|_| app.show_degraded) {
+        render_degraded(degraded, &app.keymap, frame, area);
+    }
+    panels
+}
+
+/// Renders the panels of the current tab of the selected host, or its maximized panel, and
+/// returns where they were drawn.
+fn render_host(view: &View, frame: &mut Frame, area: Rect) -> Vec<(Panel, Rect)> {
+    let app = view.app;
+    let panels = match app.maximized {
+        Some(panel) => vec![(panel, area)],
+        None => app.tab.layout(&app.config.layout).split(area),
+    };
+    for &(panel, rect) in &panels {
+        render_panel(view, panel, frame, rect);
+        if app.focused == Some(panel) {
+            highlight_border(frame, rect, app.theme.accent);
+        }
+        if let Some(severity) = view.alerts.severity(panel) {
+            highlight_border(frame, rect, severity_style(severity, &app.theme));
+        }
+    }
+    panels
+}
+
+/// Restyles the border drawn around `area`, e.g. to show the focused panel.
+fn highlight_border(frame: &mut Frame, area: Rect, style: Style) {
+    if area.is_empty() {
+        return;
+    }
+    let buffer = frame.buffer_mut();
+    for x in area.left()..area.right() {
+        buffer.get_mut(x, area.top()).set_style(style);
+        buffer.get_mut(x, area.bottom() - 1).set_style(style);
+    }
+    for y in area.top()..area.bottom() {
+        buffer.get_mut(area.left(), y).set_style(style);
+        buffer.get_mut(area.right() - 1, y).set_style(style);
+    }
+}
+
+/// Renders the host line: the "remote host" indicator on the left and the timeline position on
+/// the right.
+fn render_header(view: &View, frame: &mut Frame, area: Rect) {
+    let (app, host) = (view.app, view.host);
+    let (theme, keymap) = (&app.theme, &app.keymap);
+    let mut header = match &host.addr {
+        Some(addr) => vec![
+            Span::from(" remote host: ").bold(),
+            Span::styled(addr.to_owned(), theme.accent),
+            Span::from(" - "),
+            connection_span(&host.connection, theme),
+        ],
+        None => {
+            vec![Span::from(" host: ").bold(), Span::styled(host.name().to_owned(), theme.accent)]
+        },
+    };
+    if app.can_go_back() {
+        let hint = format!(
+            "  ({}: all hosts, {}: help)",
+            keymap.hint(Action::Back),
+            keymap.hint(Action::ToggleHelp)
+        );
+        header.push(Span::styled(hint, theme.muted));
+    }
+    let alerts = view.alerts.active();
+    if let Some(alert) = alerts.first() {
+        let text = match alerts.len() {
+            1 => " 1 alert ".to_string(),
+            count => format!(" {count} alerts "),
+        };
+        header.push(Span::from("  "));
+        header.push(Span::styled(text, severity_style(alert.severity, theme).reversed()));
+        header.push(Span::styled(format!(" ({})", keymap.hint(Action::ToggleAlerts)), theme.muted));
+    }
+    if let Some(degraded) = &host.metrics.degraded {
+        header.push(Span::from("  "));
+        header.push(Span::styled(format!(" {} ", degraded.summary()), theme.warn.reversed()));
+    }
+    if let Some(err) = &host.store_error {
+        header.push(Span::from("  "));
+        header.push(Span::styled(" history not saved ", theme.warn.reversed()));
+        header.push(Span::styled(format!(" ({err})"), theme.muted));
+    }
+    if app.editing_filter {
+        header.push(Span::from("  filter: ").bold());
+        header.push(Span::styled(format!("{}▏", app.process_filter), theme.accent));
+        header.push(Span::styled(" (enter: apply, esc: clear)", theme.muted));
+    } else if let Some(status) = &app.status {
+        header.push(Span::styled(format!("  {status}"), theme.muted));
+    }
+    frame.render_widget(Paragraph::new(Line::from(header)), area);
+
+    let history = &host.history;
+    let timeline = if app.is_live() {
+        Line::from(vec![Span::styled(" LIVE ", theme.good.reversed())])
+    } else {
+        let newest = history.snapshot(0).map_or(0.0, |m| m.timestamp);
+        let shown = history.snapshot(app.cursor).map_or(newest, |m| m.timestamp);
+        Line::from(vec![
+            Span::styled(
+                format!(
+                    "{}/{}: scrub  {}: live ",
+                    keymap.hint(Action::ScrubBack),
+                    keymap.hint(Action::ScrubForward),
+                    keymap.hint(Action::TogglePause)
+                ),
+                theme.muted,
+            ),
+            Span::styled(
+                format!(" PAUSED {} ", format_offset(newest - shown)),
+                theme.warn.reversed(),
+            ),
         ])
-        .split(layout[2]);
-    // let sub_3_layout = Layout::default()
-    //     .direction(Direction::Horizontal)
-    //     .constraints([Constraint::Percentage(50), Constraint::Percentage(50)])
-    //     .split(layout[3]);
-    // frame.render_widget(
-    //     Paragraph::new(format!(
-    //         "{}.{}.{}.{}.{}",
-    //         app.mem.total, app.mem.used, app.mem.available, app.mem.swap_total, app.mem.swap_used
-    //     ))
-    //     .block(
-    //         Block::bordered()
-    //             .title("MemoryMetrics")
-    //             .title_alignment(Alignment::Center)
-    //             .border_type(BorderType::Rounded),
-    //     )
-    //     .style(Style::default().fg(Color::Cyan).bg(Color::Black))
-    //     .centered(),
-    //     frame.size(),
-    // );
-    frame.render_widget(
-        Gauge::default()
-            .block(Block::bordered().title(format!(
-                "E-CPU Usage: {}% @{} MHz",
-                app.metrics.cpu_metrics.e_cluster_active,
-                app.metrics.cpu_metrics.e_cluster_freq_mhz
-            )))
-            .gauge_style(Style::default().on_black().green())
-            .percent(app.metrics.cpu_metrics.e_cluster_active as u16),
-        sub_0_layout[0],
-    );
-    frame.render_widget(
-        Gauge::default()
-            .block(Block::bordered().title(format!(
-                "GPU Usage: {}% @ {} MHz",
-                app.metrics.gpu_metrics.active, app.metrics.gpu_metrics.freq_mhz
-            )))
-            .gauge_style(Style::default().on_black().magenta())
-            .percent(app.metrics.gpu_metrics.active as u16),
-        sub_0_layout[1],
-    );
-    frame.render_widget(
-        Gauge::default()
-            .block(Block::bordered().title(format!(
-                "P-CPU Usage: {}% @ {} MHz",
-                app.metrics.cpu_metrics.p_cluster_active,
-                app.metrics.cpu_metrics.p_cluster_freq_mhz
-            )))
-            .gauge_style(Style::default().on_black().magenta())
-            .percent(app.metrics.cpu_metrics.p_cluster_active as u16),
-        sub_1_layout[0],
-    );
-    frame.render_widget(
-        Gauge::default()
-            .block(Block::bordered().title(format!(
-                "ANE Usage: {:.2}% @ {} W",
-                app.metrics.cpu_metrics.ane_w * 100.0 / 8.0,
-                app.metrics.cpu_metrics.ane_w
-            )))
-            .gauge_style(Style::default().on_black().magenta())
-            .percent((app.metrics.cpu_metrics.ane_w * 100.0 / 8.0) as u16),
-        sub_1_layout[1],
-    );
+    };
+    frame.render_widget(Paragraph::new(timeline).alignment(Alignment::Right), area);
+}
+
+/// Renders the tab bar, each tab titled with its key.
+fn render_tabs(app: &App, frame: &mut Frame, area: Rect) {
+    let titles =
+        Tab::ALL.iter().map(|&tab| format!("{} {}", app.keymap.hint(tab.action()), tab.title()));
+    let tabs = Tabs::new(titles)
+        .select(app.tab.index())
+        .style(app.theme.muted)
+        .highlight_style(app.theme.accent.reversed());
+    frame.render_widget(tabs, area);
+}
+
+/// Formats `value` in at most 7 characters, e.g. `12.34`, `512.0`, `1.2k` or `34.5M`.
+fn format_compact(value: f64) -> String {
+    match value.abs() {
+        magnitude if magnitude >= 1e9 => format!("{:.1}G", value / 1e9),
+        magnitude if magnitude >= 1e6 => format!("{:.1}M", value / 1e6),
+        magnitude if magnitude >= 1e4 => format!("{:.1}k", value / 1e3),
+        magnitude if magnitude >= 100.0 => format!("{value:.1}"),
+        _ => format!("{value:.2}"),
+    }
+}
+
+/// Formats a duration in its largest unit, e.g. `90s`, `2.5m`, `5m` or `1h`.
+fn format_span(seconds: f64) -> String {
+    match seconds {
+        seconds if seconds >= 7200.0 || seconds >= 3600.0 && seconds % 3600.0 == 0.0 => {
+            format!("{:.0}h", seconds / 3600.0)
+        },
+        seconds if seconds >= 120.0 || seconds >= 60.0 && seconds % 60.0 == 0.0 => {
+            let precision = if seconds % 60.0 == 0.0 { 0 } else { 1 };
+            format!("{:.*}m", precision, seconds / 60.0)
+        },
+        seconds => format!("{seconds:.0}s"),
+    }
+}
+
+/// Formats Unix `time` as a local wall-clock time, e.g. `14:03:27`.
+fn format_clock(time: f64) -> String {
+    let time = time as libc::time_t;
+    // SAFETY: `tm` is plain integers and a pointer, for which all zeroes is a valid value.
+    let mut tm = unsafe { std::mem::zeroed::<libc::tm>() };
+    // SAFETY: both pointers come from live references, `localtime_r` is reentrant and only
+    // writes the `tm` it is given.
+    let converted = unsafe { libc::localtime_r(&time, &mut tm) };
+    if converted.is_null() {
+        // A time the C library can't convert, show it in UTC rather than midnight.
+        let seconds = time.rem_euclid(86_400);
+        return format!("{:02}:{:02}:{:02}", seconds / 3600, seconds / 60 % 60, seconds % 60);
+    }
+    format!("{:02}:{:02}:{:02}", tm.tm_hour, tm.tm_min, tm.tm_sec)
+}
 
-    // apple silicon list
-    let binding = "Unknown Model".to_string();
-    let name = app.metrics.soc_info.get("name").unwrap_or(&binding);
-    let binding = "0".to_string();
-    let e_cores = app.metrics.soc_info.get("e_core_count").unwrap_or(&binding);
-    let binding = "0".to_string();
-    let p_cores = app.metrics.soc_info.get("p_core_count").unwrap_or(&binding);
-    let total_cores =
-        (e_cores.parse::<i32>().unwrap() + p_cores.parse::<i32>().unwrap()).to_string();
-    let gpu_cores = app.metrics.soc_info.get("gpu_core_count").unwrap();
-    let apple_silicon_item = [
-        name.to_owned(),
-        format!("Total cores: {total_cores}"),
-        format!("E-Cores: {e_cores}"),
-        format!("P-Cores: {p_cores}"),
-        format!("GPU Cores: {gpu_cores}"),
-    ];
-    let apple_silicon_list =
-        List::new(apple_silicon_item).block(Block::bordered().title("Apple Silicon"));
-    frame.render_widget(apple_silicon_list, sub_2_layout[0]);
-
-    // net work list
-    let network_item = [
-        format!(
-            "Out: {:.1} packets/s, {:.1} bytes/s",
-            app.metrics.net_disk_metrics.out_packets_per_sec,
-            app.metrics.net_disk_metrics.out_bytes_per_sec
+/// Formats how far back the cursor is, e.g. `-00:42` or `-1:02:03`.
+fn format_offset(seconds: f64) -> String {
+    let seconds = seconds.max(0.0).round() as u64;
+    let (hours, minutes, seconds) = (seconds / 3600, seconds / 60 % 60, seconds % 60);
+    if hours > 0 {
+        format!("-{hours}:{minutes:02}:{seconds:02}")
+    } else {
+        format!("-{minutes:02}:{seconds:02}")
+    }
+}
+
+fn connection_span(connection: &ConnectionState, theme: &Theme) -> Span<'static> {
+    match connection {
+        ConnectionState::Connecting => Span::styled("connecting", theme.warn),
+        ConnectionState::Connected => Span::styled("connected", theme.good),
+        ConnectionState::Lost(reason) => {
+            Span::styled(format!("reconnecting ({reason})"), theme.bad)
+        },
+    }
+}
+
+/// Renders one compact row per host.
+fn render_dashboard(app: &App, frame: &mut Frame, area: Rect) {
+    let theme = &app.theme;
+    let header =
+        Row::new(["Host", "Chip", "E-CPU", "P-CPU", "GPU", "Package", "Memory", "Status"]).bold();
+    let rows = app.hosts.iter().enumerate().map(|(index, host)| {
+        let metrics = &host.metrics;
+        let status = match (&host.connection, &metrics.degraded) {
+            (ConnectionState::Connected, Some(degraded)) => {
+                Span::styled(format!("degraded ({})", degraded.summary()), theme.warn)
+            },
+            (connection, _) => connection_span(connection, theme),
+        };
+        let status = Cell::from(Line::from(status));
+        // Without powermetrics there is no GPU nor power reading, not zeros.
+        let measured = |value: Cell<'static>| match metrics.degraded {
+            Some(_) => Cell::from("-"),
+            None => value,
+        };
+        let mut row = if host.has_metrics() {
+            let chip = Some(metrics.soc_info.model.as_str()).filter(|model| !model.is_empty());
+            let chip = chip.unwrap_or("?");
+            let mem = metrics.mem.used as f64 * 100.0 / metrics.mem.total.max(1) as f64;
+            Row::new([
+                Cell::from(host.name().to_owned()),
+                Cell::from(chip.to_owned()),
+                usage_cell(metrics.cpu_metrics.e_cluster_active as f64, theme),
+                usage_cell(metrics.cpu_metrics.p_cluster_active as f64, theme),
+                measured(usage_cell(metrics.gpu_metrics.active, theme)),
+                measured(Cell::from(format!("{:.2} W", metrics.cpu_metrics.package_w))),
+                usage_cell(mem, theme),
+                status,
+            ])
+        } else {
+            Row::new([
+                Cell::from(host.name().to_owned()),
+                Cell::from("-"),
+                Cell::from("-"),
+                Cell::from("-"),
+                Cell::from("-"),
+                Cell::from("-"),
+                Cell::from("-"),
+                status,
+            ])
+        };
+        if index == app.selected {
+            row = row.reversed();
+        }
+        row
+    });
+    let table = Table::new(
+        rows,
+        [
+            Constraint::Percentage(20),
+            Constraint::Percentage(20),
+            Constraint::Length(7),
+            Constraint::Length(7),
+            Constraint::Length(7),
+            Constraint::Length(9),
+            Constraint::Length(7),
+            Constraint::Min(10),
+        ],
+    )
+    .header(header)
+    .block(Block::bordered().title(format!(
+        "{} hosts ({}/{}: select, {}: details, {}: help, {}: quit)",
+        app.hosts.len(),
+        app.keymap.hint(Action::SelectPrevious),
+        app.keymap.hint(Action::SelectNext),
+        app.keymap.hint(Action::DrillDown),
+        app.keymap.hint(Action::ToggleHelp),
+        app.keymap.hint(Action::Quit)
+    )));
+    frame.render_widget(table, area);
+}
+
+/// Style of alerts of `severity`.
+fn severity_style(severity: Severity, theme: &Theme) -> Style {
+    match severity {
+        Severity::Info => theme.accent,
+        Severity::Warning => theme.warn,
+        Severity::Critical => theme.bad,
+    }
+}
+
+/// A percentage colored by how busy it is, so throttling or idle hosts stand out.
+fn usage_cell(percent: f64, theme: &Theme) -> Cell<'static> {
+    let style = match percent {
+        p if p >= 80.0 => theme.bad,
+        p if p >= 50.0 => theme.warn,
+        _ => theme.good,
+    };
+    Cell::from(Span::styled(format!("{percent:.0}%"), style))
+}
+
+/// Renders min/avg/max/p95 of every metric over the `window` ending at `end` in a popup.
+fn render_stats_table(
+    history: &History,
+    window: StatsWindow,
+    end: f64,
+    keymap: &Keymap,
+    frame: &mut Frame,
+    area: Rect,
+) {
+    let area = centered(area, 80, 80);
+    let header = Row::new(["Metric", "Now", "Min", "Avg", "Max", "P95", "Unit"]).bold();
+    let rows = MetricKey::ALL.iter().map(|&key| {
+        let now = history.value_at(key, end).map(|value| format!("{value:.2}")).unwrap_or_default();
+        let stats = history.series(key).stats(window, end);
+        let cell =
+            |value: Option<f64>| value.map(|value| format!("{value:.2}")).unwrap_or_default();
+        Row::new([
+            key.label().to_string(),
+            now,
+            cell(stats.map(|s| s.min)),
+            cell(stats.map(|s| s.mean)),
+            cell(stats.map(|s| s.max)),
+            cell(stats.map(|s| s.p95)),
+            key.unit().to_string(),
+        ])
+    });
+    let table = Table::new(
+        rows,
+        [
+            Constraint::Length(20),
+            Constraint::Length(11),
+            Constraint::Length(11),
+            Constraint::Length(11),
+            Constraint::Length(11),
+            Constraint::Length(11),
+            Constraint::Min(10),
+        ],
+    )
+    .header(header)
+    .block(Block::bordered().title(format!(
+        "Statistics over {} ({}: change window, {}: close)",
+        window.label(),
+        keymap.hint(Action::NextStatsWindow),
+        keymap.hint(Action::ToggleStats)
+    )));
+    frame.render_widget(Clear, area);
+    frame.render_widget(table, area);
+}
+
+/// Renders the session energy up to the cursor, cost and emissions per rail and per process in a popup.
+fn render_energy_table(
+    energy: &EnergyMeter,
+    tariff: &TariffConfig,
+    keymap: &Keymap,
+    frame: &mut Frame,
+    area: Rect,
+) {
+    let area = centered(area, 80, 80);
+    let table = energy_table(energy, tariff).block(Block::bordered().title(format!(
+        "Energy over {}, processes estimated from energy impact ({}: close)",
+        format_duration(energy.elapsed()),
+        keymap.hint(Action::ToggleEnergy)
+    )));
+    frame.render_widget(Clear, area);
+    frame.render_widget(table, area);
+}
+
+/// Renders the alerts of the selected host active at the cursor in a popup.
+fn render_alert_table(view: &View, frame: &mut Frame, area: Rect) {
+    let (app, area) = (view.app, centered(area, 80, 60));
+    let table =
+        alert_table(&view.alerts, view.end, &app.theme).block(Block::bordered().title(format!(
+            "Alerts, set in [alerts] of the config ({}: close)",
+            app.keymap.hint(Action::ToggleAlerts)
+        )));
+    frame.render_widget(Clear, area);
+    frame.render_widget(table, area);
+}
+
+/// Explains in a popup why `powermetrics` can't run and what is measured without it.
+fn render_degraded(degraded: &Degraded, keymap: &Keymap, frame: &mut Frame, area: Rect) {
+    let area = centered(area, 70, 60);
+    let source = "CPU, GPU and ANE power, frequencies, disk activity and processes are read from \
+                  powermetrics";
+    let (title, mut text) = match degraded {
+        Degraded::NotRoot => (
+            "powermetrics needs root",
+            vec![
+                Line::from(format!("{source}, which only runs as root. Start mactop-rs with:")),
+                Line::from(""),
+                Line::from(Span::from("    sudo mactop-rs").bold()),
+            ],
         ),
-        format!(
-            "In: {:.1} packets/s, {:.1} bytes/s",
-            app.metrics.net_disk_metrics.in_packets_per_sec,
-            app.metrics.net_disk_metrics.in_bytes_per_sec
+        Degraded::Missing => (
+            "powermetrics not found",
+            vec![Line::from(format!(
+                "{source}, which ships with macOS in /usr/bin and isn't on the PATH. On another \
+                 system, run `mactop-rs agent` on the Mac and connect to it with \
+                 `mactop-rs --remote HOST:PORT`."
+            ))],
         ),
-        format!(
-            "Read: {:.1} ops/s, {:.1} KBytes/s",
-            app.metrics.net_disk_metrics.read_ops_per_sec,
-            app.metrics.net_disk_metrics.read_k_bytes_per_sec
+        Degraded::Failed(error) => (
+            "powermetrics failed",
+            vec![
+                Line::from(format!("{source}, which failed with:")),
+                Line::from(""),
+                Line::from(Span::from(format!("    {error}")).bold()),
+            ],
         ),
-        format!(
-            "Write: {:.1} ops/s, {:.1} KBytes/s",
-            app.metrics.net_disk_metrics.write_ops_per_sec,
-            app.metrics.net_disk_metrics.write_k_bytes_per_sec
+        Degraded::HelperFailed(error) => (
+            "root helper failed",
+            vec![
+                Line::from(format!(
+                    "{source}, which only runs as root. The collector started as root with sudo \
+                     stopped:"
+                )),
+                Line::from(""),
+                Line::from(Span::from(format!("    {error}")).bold()),
+                Line::from(""),
+                Line::from(
+                    "If sudo asked for a password again, run `sudo -v` and restart mactop-rs, or \
+                     start it with `sudo mactop-rs`.",
+                ),
+            ],
         ),
-    ];
-
-    let network_list =
-        List::new(network_item).block(Block::bordered().title("NetWork & Disk Info"));
-    frame.render_widget(network_list, sub_2_layout[1]);
-
-    // cpu-gpu list
-    let cpu_w = app.metrics.cpu_metrics.cpu_w;
-    let gpu_w = app.metrics.cpu_metrics.gpu_w;
-    let ane_w = app.metrics.cpu_metrics.ane_w;
-    let package_w = app.metrics.cpu_metrics.package_w;
-    let cpu_gpu_item = [
-        format!("CPU Power: {cpu_w} W"),
-        format!("GPU Power: {gpu_w} W"),
-        format!("ANE Power: {ane_w} W"),
-        format!("Total Power: {package_w} W"),
-    ];
-
-    let network_list = List::new(cpu_gpu_item)
-        .block(Block::bordered().title(format!("{} W CPU - {} W GPU", cpu_w, gpu_w)));
-    frame.render_widget(network_list, sub_2_layout[2]);
-
-    // total power
-    let power_data: Vec<(f64, f64)> = app
-        .cpu_w
-        .iter()
-        .enumerate()
-        .map(|(index, &value)| (index as f64, value))
-        .collect();
-    let power_dataset = vec![Dataset::default()
-        .marker(symbols::Marker::Dot)
-        .style(Style::default().on_black().cyan())
-        .data(&power_data[..])];
-    let y_max = app.cpu_w.iter().copied().reduce(f64::max).unwrap_or(0.01);
-    let power_chat = Chart::new(power_dataset)
+    };
+    text.extend([
+        Line::from(""),
+        Line::from(
+            "Until then only memory, CPU usage and network traffic are measured, the other \
+             panels say so instead of showing zeros.",
+        ),
+    ]);
+    let paragraph = Paragraph::new(text).wrap(Wrap { trim: false }).block(
+        Block::bordered().title(format!("{title} ({}: continue)", keymap.hint(Action::Back))),
+    );
+    frame.render_widget(Clear, area);
+    frame.render_widget(paragraph, area);
+}
+
+/// Renders every action and its keys in a popup.
+fn render_help(keymap: &Keymap, frame: &mut Frame, area: Rect) {
+    let area = centered(area, 70, 80);
+    let header = Row::new(["Keys", "Action"]).bold();
+    let rows = Action::ALL.iter().map(|&action| {
+        let keys = keymap.keys(action).iter().map(ToString::to_string).collect::<Vec<_>>();
+        Row::new([keys.join(", "), action.description().to_string()])
+    });
+    let mouse = [
+        ("click", "Focus a panel"),
+        ("double-click", "Maximize or restore a panel"),
+        ("wheel", "Scroll the process list, or history"),
+        ("hover", "Show the values under a chart"),
+    ]
+    .map(|(input, description)| Row::new([input, description]));
+    let rows = rows.chain(mouse);
+    let table = Table::new(rows, [Constraint::Length(24), Constraint::Min(20)])
+        .header(header)
         .block(Block::bordered().title(format!(
-            "{:.2} W Total Power",
-            app.cpu_w.last().unwrap_or(&0.0)
-        )))
-        .x_axis(Axis::default().bounds([0.0, 24.0]).labels(vec![
-            "0".into(),
-            // // "3".into(),
-            // "5".into(),
-            // // "9".into(),
-            // "10".into(),
-            // "15".into(),
-            // "20".into(),
-            "24".into(),
-        ]))
-        .y_axis(
-            Axis::default()
-                .bounds([0.0, y_max])
-                .labels(vec!["0".into(), format!("{:.2}", y_max).into()]),
-        );
-    frame.render_widget(power_chat, sub_2_layout[3]);
-
-    // memory usage
-    frame.render_widget(
-        Gauge::default()
-            .block(Block::bordered().title(format!(
-                "Memory Usage: {:.2} GB / {:.2} GB (Swap: {:.2}/{:.2} GB)",
-                app.metrics.mem.used as f64 / 1024.0 / 1024.0 / 1024.0,
-                app.metrics.mem.total as f64 / 1024.0 / 1024.0 / 1024.0,
-                app.metrics.mem.swap_used as f64 / 1024.0 / 1024.0 / 1024.0,
-                app.metrics.mem.swap_total as f64 / 1024.0 / 1024.0 / 1024.0,
-            )))
-            .gauge_style(Style::default().on_black().green())
-            .percent(((app.metrics.mem.used as f64 / app.metrics.mem.total as f64) * 100.0) as u16),
-        layout[3],
-    )
+            "Key bindings, set in [keys] of the config ({}: close)",
+            keymap.hint(Action::ToggleHelp)
+        )));
+    frame.render_widget(Clear, area);
+    frame.render_widget(table, area);
+}
+
+/// A `percent_x` by `percent_y` rectangle in the middle of `area`.
+fn centered(area: Rect, percent_x: u16, percent_y: u16) -> Rect {
+    let vertical = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([
+            Constraint::Percentage((100 - percent_y) / 2),
+            Constraint::Percentage(percent_y),
+            Constraint::Percentage((100 - percent_y) / 2),
+        ])
+        .split(area);
+    Layout::default()
+        .direction(Direction::Horizontal)
+        .constraints([
+            Constraint::Percentage((100 - percent_x) / 2),
+            Constraint::Percentage(percent_x),
+            Constraint::Percentage((100 - percent_x) / 2),
+        ])
+        .split(vertical[1])[1]
 }
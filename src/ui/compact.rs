@@ -0,0 +1,111 @@
+use ratatui::{
+    layout::Rect,
+    style::{Style, Stylize},
+    text::{Line, Span},
+    widgets::{Paragraph, Sparkline},
+    Frame,
+};
+
+use super::{format_compact, panels::View};
+use crate::{history::MetricKey, layout::Panel};
+
+/// Metrics of the compact layout, one per line, with the panel whose colors they take.
+const METRICS: [(&str, MetricKey, Panel); 13] = [
+    ("E-CPU %", MetricKey::ECpuActive, Panel::ECpu),
+    ("P-CPU %", MetricKey::PCpuActive, Panel::PCpu),
+    ("GPU %", MetricKey::GpuActive, Panel::Gpu),
+    ("CPU W", MetricKey::CpuPower, Panel::PowerChart),
+    ("GPU W", MetricKey::GpuPower, Panel::PowerChart),
+    ("ANE W", MetricKey::AnePower, Panel::Ane),
+    ("Total W", MetricKey::PackagePower, Panel::PowerChart),
+    ("Mem GB", MetricKey::MemoryUsed, Panel::Memory),
+    ("Swap GB", MetricKey::SwapUsed, Panel::Memory),
+    ("Net in B/s", MetricKey::NetInBytes, Panel::NetDisk),
+    ("Net out B/s", MetricKey::NetOutBytes, Panel::NetDisk),
+    ("Read KB/s", MetricKey::DiskReadKBytes, Panel::NetDisk),
+    ("Write KB/s", MetricKey::DiskWriteKBytes, Panel::NetDisk),
+];
+
+/// Width of the label and value in front of each sparkline.
+const TEXT_WIDTH: u16 = 20;
+
+/// Sparklines narrower than this are not drawn.
+const MIN_SPARKLINE_WIDTH: u16 = 5;
+
+/// Renders one line per metric with a sparkline of its recent history, then the top processes in
+/// the lines left.
+///
+/// Without `powermetrics`, its metrics show `-` rather than zeros that look like an idle machine.
+pub fn render_compact(view: &View, frame: &mut Frame, area: Rect) {
+    let lines = METRICS.len().min(area.height as usize);
+    for (index, &(label, key, panel)) in METRICS.iter().take(lines).enumerate() {
+        let line = Rect { y: area.y + index as u16, height: 1, ..area };
+        let unavailable = view.metrics.degraded.is_some() && key.needs_powermetrics();
+        let value = if unavailable { "-".to_string() } else { format_compact(view.value(key)) };
+        let text = Line::from(vec![
+            Span::from(format!("{label:<11}")).bold(),
+            Span::from(format!("{value:>8} ")),
+        ]);
+        frame.render_widget(Paragraph::new(text), line);
+        if unavailable {
+            continue;
+        }
+
+        let sparkline_area =
+            Rect { x: line.x + TEXT_WIDTH, width: line.width.saturating_sub(TEXT_WIDTH), ..line };
+        if sparkline_area.width < MIN_SPARKLINE_WIDTH {
+            continue;
+        }
+        // Sparklines take integers, keep two decimals.
+        let data: Vec<u64> = view
+            .history
+            .series(key)
+            .tail(sparkline_area.width as usize, view.end)
+            .map(|sample| (sample.value.max(0.0) * 100.0) as u64)
+            .collect();
+        let max = match full_scale(view, key) {
+            Some(max) => (max * 100.0) as u64,
+            None => data.iter().copied().max().unwrap_or_default(),
+        };
+        let style = view.style(panel);
+        let style = Style::default().fg(style.fg.or(view.app.theme.accent.fg).unwrap_or_default());
+        frame.render_widget(
+            Sparkline::default().data(&data).max(max.max(1)).style(style),
+            sparkline_area,
+        );
+    }
+
+    let processes = view.app.processes(view.metrics);
+    let rows = (area.height as usize).saturating_sub(lines + 1);
+    if rows == 0 {
+        return;
+    }
+    let header = Rect { y: area.y + lines as u16, height: 1, ..area };
+    frame.render_widget(Paragraph::new("Process      Impact   CPU %").bold(), header);
+    for (index, process) in processes.iter().take(rows).enumerate() {
+        let line = Rect { y: header.y + 1 + index as u16, height: 1, ..area };
+        let text = format!(
+            "{:<11}{:>8} {:>7.1}",
+            truncate(&process.name, 10),
+            format!("{:.1}", process.energy_impact),
+            process.cpu_percent()
+        );
+        frame.render_widget(Paragraph::new(text).style(view.style(Panel::Processes)), line);
+    }
+}
+
+/// Value drawn as a full sparkline bar, `None` to scale to the highest sample.
+fn full_scale(view: &View, key: MetricKey) -> Option<f64> {
+    let mem = &view.metrics.mem;
+    match key {
+        MetricKey::ECpuActive | MetricKey::PCpuActive | MetricKey::GpuActive => Some(100.0),
+        MetricKey::MemoryUsed => Some(mem.total as f64 / 1024.0 / 1024.0 / 1024.0),
+        MetricKey::SwapUsed => Some(mem.swap_total as f64 / 1024.0 / 1024.0 / 1024.0),
+        _ => None,
+    }
+}
+
+/// The first `width` characters of `text`.
+fn truncate(text: &str, width: usize) -> String {
+    text.chars().take(width).collect()
+}
@@ -0,0 +1,761 @@
+use std::borrow::Cow;
+
+use ratatui::{
+    layout::{Alignment, Constraint, Direction, Layout, Position, Rect},
+    style::{Style, Stylize},
+    symbols,
+    text::{Line, Span},
+    widgets::{
+        block::Title, Axis, Block, Cell, Chart, Clear, Dataset, Gauge, GraphType, List, Paragraph,
+        Row, Table, Wrap,
+    },
+    Frame,
+};
+
+use super::{format_clock, format_compact, format_span, severity_style};
+use crate::{
+    alert::Alerts,
+    app::{App, ProcessSort},
+    chip::ChipSpec,
+    config::{TariffConfig, TimeAxis},
+    energy::{format_duration, top_consumers, EnergyMeter, Rail},
+    history::{History, MetricKey},
+    host::Host,
+    layout::Panel,
+    metrics::{Degraded, Metrics},
+    stats::{Bucket, StatsWindow},
+    theme::Theme,
+};
+
+/// What the panels show: the selected host as of the timeline cursor.
+pub struct View<'a> {
+    pub app: &'a App,
+    pub host: &'a Host,
+    pub history: &'a History,
+    /// Snapshot at the cursor.
+    pub metrics: &'a Metrics,
+    /// Time of [`View::metrics`], the end of every chart and statistic.
+    pub end: f64,
+    pub window: StatsWindow,
+    /// Peak figures of the host's chip, the full scale of gauges.
+    pub chip: ChipSpec,
+    /// Energy used up to the cursor.
+    pub energy: Cow<'a, EnergyMeter>,
+    /// Alerts active at the cursor.
+    pub alerts: Cow<'a, Alerts>,
+}
+
+impl<'a> View<'a> {
+    pub fn new(app: &'a App) -> Self {
+        let host = app.host();
+        let history = &host.history;
+        let snapshot = history.snapshot(app.cursor);
+        let end = snapshot.map_or(host.metrics.timestamp, |metrics| metrics.timestamp);
+        // Live, only samples received since startup are shown, not the reloaded history.
+        let metrics = if app.is_live() { &host.metrics } else { snapshot.unwrap_or(&host.metrics) };
+        let chip = app.config.chip.resolve(&metrics.soc_info.model);
+        let (energy, alerts) = if app.is_live() {
+            (Cow::Borrowed(&host.energy), Cow::Borrowed(&host.alerts))
+        } else {
+            (Cow::Owned(host.energy.at(history, end)), host.alerts_at(app.cursor))
+        };
+        Self { app, host, history, metrics, end, window: app.stats_window, chip, energy, alerts }
+    }
+
+    /// Style of `panel` in the current theme.
+    pub fn style(&self, panel: Panel) -> Style {
+        self.app.theme.panel(panel)
+    }
+
+    /// Value of `key` at the cursor.
+    pub fn value(&self, key: MetricKey) -> f64 {
+        key.value(self.metrics)
+    }
+
+    /// Right-aligned title summarizing `key` over the stats window.
+    fn stats_title(&self, key: MetricKey) -> Title<'static> {
+        stats_title(self.history, key, self.window, self.end)
+    }
+}
+
+/// Renders `panel` into `area`.
+pub fn render_panel(view: &View, panel: Panel, frame: &mut Frame, area: Rect) {
+    if let Some(degraded) = view.metrics.degraded.as_ref().filter(|_| panel.needs_powermetrics()) {
+        render_unavailable(view, panel, degraded, frame, area);
+        return;
+    }
+    match panel {
+        Panel::ECpu => render_e_cpu(view, frame, area),
+        Panel::PCpu => render_p_cpu(view, frame, area),
+        Panel::Gpu => render_gpu(view, frame, area),
+        Panel::Ane => render_ane(view, frame, area),
+        Panel::SocInfo => render_soc_info(view, frame, area),
+        Panel::NetDisk => render_net_disk(view, frame, area),
+        Panel::PowerList => render_power_list(view, frame, area),
+        Panel::PowerChart
+        | Panel::CpuChart
+        | Panel::GpuChart
+        | Panel::FreqChart
+        | Panel::RailsChart
+        | Panel::MemoryChart
+        | Panel::NetChart
+        | Panel::DiskChart => render_chart(view, panel, frame, area),
+        Panel::Memory => render_memory(view, frame, area),
+        Panel::Processes => render_processes(view, frame, area),
+        Panel::CpuClusters => render_cpu_clusters(view, frame, area),
+        Panel::CpuCores => render_cpu_cores(view, frame, area),
+        Panel::GpuInfo => render_gpu_info(view, frame, area),
+        Panel::Energy => render_energy(view, frame, area),
+        Panel::TopConsumers => render_top_consumers(view, frame, area),
+        Panel::Alerts => render_alerts(view, frame, area),
+    }
+}
+
+/// Renders why `panel` is empty instead of zeros that look like an idle machine.
+fn render_unavailable(
+    view: &View,
+    panel: Panel,
+    degraded: &Degraded,
+    frame: &mut Frame,
+    area: Rect,
+) {
+    let hint = match degraded {
+        Degraded::NotRoot => "Needs powermetrics, run with sudo".to_string(),
+        Degraded::Missing => "Needs powermetrics, only on macOS".to_string(),
+        Degraded::Failed(error) => format!("Needs powermetrics: {error}"),
+        Degraded::HelperFailed(_) => "Needs powermetrics, the root helper stopped".to_string(),
+    };
+    let paragraph = Paragraph::new(Line::styled(hint, view.app.theme.muted))
+        .alignment(Alignment::Center)
+        .wrap(Wrap { trim: true })
+        .style(view.style(panel))
+        .block(Block::bordered().title(format!("{}: no data", panel.title())));
+    frame.render_widget(paragraph, area);
+}
+
+/// Right-aligned title summarizing `key` over the `window` ending at `end`, empty without samples.
+fn stats_title(history: &History, key: MetricKey, window: StatsWindow, end: f64) -> Title<'static> {
+    let text = match history.series(key).stats(window, end) {
+        Some(stats) => format!(
+            " {} avg {:.1} max {:.1} p95 {:.1} {} ",
+            window.label(),
+            stats.mean,
+            stats.max,
+            stats.p95,
+            key.unit()
+        ),
+        None => String::new(),
+    };
+    Title::from(text).alignment(Alignment::Right)
+}
+
+/// Neural engine power taken as full load when the chip is unknown.
+const ANE_PEAK_W: f64 = 8.0;
+
+/// Gauge of a cluster `active` percent of the time at `freq_mhz`.
+///
+/// With the highest frequency known, the gauge fills to the share of the peak throughput in use,
+/// so a busy cluster at a low clock isn't shown full.
+fn cluster_gauge(active: f64, freq_mhz: i64, max_freq_mhz: Option<u32>) -> Gauge<'static> {
+    match max_freq_mhz.filter(|&max| max > 0 && freq_mhz > 0) {
+        Some(max) => {
+            let load = active * (freq_mhz as f64 / max as f64).min(1.0);
+            Gauge::default()
+                .percent(load.clamp(0.0, 100.0) as u16)
+                .label(format!("{load:.0}% of peak"))
+        },
+        None => Gauge::default().percent(active.clamp(0.0, 100.0) as u16),
+    }
+}
+
+/// ` of {max} MHz` when the highest frequency is known.
+fn of_max_mhz(max_freq_mhz: Option<u32>) -> String {
+    max_freq_mhz.map_or_else(String::new, |max| format!(" of {max}"))
+}
+
+fn render_e_cpu(view: &View, frame: &mut Frame, area: Rect) {
+    let cpu_metrics = &view.metrics.cpu_metrics;
+    let max_freq_mhz = view.chip.e_freq_mhz;
+    frame.render_widget(
+        cluster_gauge(
+            cpu_metrics.e_cluster_active as f64,
+            cpu_metrics.e_cluster_freq_mhz,
+            max_freq_mhz,
+        )
+        .block(
+            Block::bordered()
+                .title(format!(
+                    "E-CPU Usage: {}% @{}{} MHz",
+                    cpu_metrics.e_cluster_active,
+                    cpu_metrics.e_cluster_freq_mhz,
+                    of_max_mhz(max_freq_mhz)
+                ))
+                .title(view.stats_title(MetricKey::ECpuActive)),
+        )
+        .gauge_style(view.style(Panel::ECpu)),
+        area,
+    );
+}
+
+fn render_p_cpu(view: &View, frame: &mut Frame, area: Rect) {
+    let cpu_metrics = &view.metrics.cpu_metrics;
+    let max_freq_mhz = view.chip.p_freq_mhz;
+    frame.render_widget(
+        cluster_gauge(
+            cpu_metrics.p_cluster_active as f64,
+            cpu_metrics.p_cluster_freq_mhz,
+            max_freq_mhz,
+        )
+        .block(
+            Block::bordered()
+                .title(format!(
+                    "P-CPU Usage: {}% @ {}{} MHz",
+                    cpu_metrics.p_cluster_active,
+                    cpu_metrics.p_cluster_freq_mhz,
+                    of_max_mhz(max_freq_mhz)
+                ))
+                .title(view.stats_title(MetricKey::PCpuActive)),
+        )
+        .gauge_style(view.style(Panel::PCpu)),
+        area,
+    );
+}
+
+fn render_gpu(view: &View, frame: &mut Frame, area: Rect) {
+    let gpu_metrics = &view.metrics.gpu_metrics;
+    let max_freq_mhz = view.chip.gpu_freq_mhz;
+    frame.render_widget(
+        cluster_gauge(gpu_metrics.active, gpu_metrics.freq_mhz, max_freq_mhz)
+            .block(
+                Block::bordered()
+                    .title(format!(
+                        "GPU Usage: {}% @ {}{} MHz",
+                        gpu_metrics.active,
+                        gpu_metrics.freq_mhz,
+                        of_max_mhz(max_freq_mhz)
+                    ))
+                    .title(view.stats_title(MetricKey::GpuActive)),
+            )
+            .gauge_style(view.style(Panel::Gpu)),
+        area,
+    );
+}
+
+fn render_ane(view: &View, frame: &mut Frame, area: Rect) {
+    let ane_w = view.metrics.cpu_metrics.ane_w;
+    let percent = ane_w * 100.0 / view.chip.ane_peak_w.unwrap_or(ANE_PEAK_W);
+    frame.render_widget(
+        Gauge::default()
+            .block(
+                Block::bordered()
+                    .title(format!("ANE Usage: {percent:.2}% @ {ane_w} W"))
+                    .title(view.stats_title(MetricKey::AnePower)),
+            )
+            .gauge_style(view.style(Panel::Ane))
+            .percent(percent.clamp(0.0, 100.0) as u16),
+        area,
+    );
+}
+
+fn render_soc_info(view: &View, frame: &mut Frame, area: Rect) {
+    let soc_info = &view.metrics.soc_info;
+    let name = if soc_info.model.is_empty() { "Unknown Model" } else { &soc_info.model };
+    let gpu_cores = soc_info.gpu_core_count.or(view.chip.gpu_cores);
+    let gpu_cores = gpu_cores.map_or("?".to_string(), |cores| cores.to_string());
+    let mut apple_silicon_item = vec![
+        name.to_owned(),
+        format!("Total cores: {}", soc_info.core_count),
+        format!("E-Cores: {}", soc_info.e_core_count),
+        format!("P-Cores: {}", soc_info.p_core_count),
+        format!("GPU Cores: {gpu_cores}"),
+    ];
+    if !view.metrics.thermal_pressure.is_empty() {
+        apple_silicon_item.push(format!("Thermal: {}", view.metrics.thermal_pressure));
+    }
+    if soc_info.memory_bytes > 0 {
+        let gb = soc_info.memory_bytes as f64 / 1024.0 / 1024.0 / 1024.0;
+        apple_silicon_item.push(format!("Memory: {gb:.0} GB"));
+    }
+    if !soc_info.os_version.is_empty() {
+        apple_silicon_item.push(soc_info.os_version.clone());
+    }
+    let apple_silicon_list = List::new(apple_silicon_item)
+        .style(view.style(Panel::SocInfo))
+        .block(Block::bordered().title("Apple Silicon"));
+    frame.render_widget(apple_silicon_list, area);
+}
+
+fn render_net_disk(view: &View, frame: &mut Frame, area: Rect) {
+    let net_disk_metrics = &view.metrics.net_disk_metrics;
+    let network_item = [
+        format!(
+            "Out: {:.1} packets/s, {:.1} bytes/s",
+            net_disk_metrics.out_packets_per_sec, net_disk_metrics.out_bytes_per_sec
+        ),
+        format!(
+            "In: {:.1} packets/s, {:.1} bytes/s",
+            net_disk_metrics.in_packets_per_sec, net_disk_metrics.in_bytes_per_sec
+        ),
+        format!(
+            "Read: {:.1} ops/s, {:.1} KBytes/s",
+            net_disk_metrics.read_ops_per_sec, net_disk_metrics.read_k_bytes_per_sec
+        ),
+        format!(
+            "Write: {:.1} ops/s, {:.1} KBytes/s",
+            net_disk_metrics.write_ops_per_sec, net_disk_metrics.write_k_bytes_per_sec
+        ),
+    ];
+
+    let network_list = List::new(network_item)
+        .style(view.style(Panel::NetDisk))
+        .block(Block::bordered().title("NetWork & Disk Info"));
+    frame.render_widget(network_list, area);
+}
+
+fn render_power_list(view: &View, frame: &mut Frame, area: Rect) {
+    let cpu_w = view.value(MetricKey::CpuPower);
+    let gpu_w = view.value(MetricKey::GpuPower);
+    let ane_w = view.value(MetricKey::AnePower);
+    let package_w = view.value(MetricKey::PackagePower);
+    let joules = |rail: Rail| view.energy.joules(rail);
+    let cpu_gpu_item = [
+        format!("CPU Power: {cpu_w} W · {:.4} Wh", joules(Rail::Cpu) / 3600.0),
+        format!("GPU Power: {gpu_w} W · {:.4} Wh", joules(Rail::Gpu) / 3600.0),
+        format!("ANE Power: {ane_w} W · {:.4} Wh", joules(Rail::Ane) / 3600.0),
+        format!("Total Power: {package_w} W · {:.4} Wh", joules(Rail::Package) / 3600.0),
+        format!("Energy over {}", format_duration(view.energy.elapsed())),
+        format!("Cost: {}", view.app.config.tariff.format(joules(Rail::Package))),
+    ];
+
+    let power_list = List::new(cpu_gpu_item)
+        .style(view.style(Panel::PowerList))
+        .block(Block::bordered().title(format!("{} W CPU - {} W GPU", cpu_w, gpu_w)));
+    frame.render_widget(power_list, area);
+}
+
+/// Markers telling the series of a chart apart when the theme has no colors for them.
+const MARKERS: [symbols::Marker; 4] =
+    [symbols::Marker::Braille, symbols::Marker::Dot, symbols::Marker::Block, symbols::Marker::Bar];
+
+/// Title, series and full-scale value of a chart panel, `None` to fit the samples.
+fn chart_spec(view: &View, panel: Panel) -> (&'static str, &'static [MetricKey], Option<f64>) {
+    let gb = |bytes: u64| bytes as f64 / 1024.0 / 1024.0 / 1024.0;
+    let chip = &view.chip;
+    let max_freq_mhz = [chip.e_freq_mhz, chip.p_freq_mhz, chip.gpu_freq_mhz].into_iter().max();
+    match panel {
+        Panel::CpuChart => {
+            ("CPU usage", &[MetricKey::ECpuActive, MetricKey::PCpuActive], Some(100.0))
+        },
+        Panel::GpuChart => ("GPU usage", &[MetricKey::GpuActive], Some(100.0)),
+        Panel::FreqChart => {
+            let keys = &[MetricKey::ECpuFreq, MetricKey::PCpuFreq, MetricKey::GpuFreq];
+            ("Frequency", keys, max_freq_mhz.flatten().map(f64::from))
+        },
+        Panel::RailsChart => {
+            let keys = &[MetricKey::CpuPower, MetricKey::GpuPower, MetricKey::AnePower];
+            ("Power rails", keys, chip.tdp_w)
+        },
+        Panel::MemoryChart => (
+            "Memory",
+            &[MetricKey::MemoryUsed, MetricKey::SwapUsed],
+            Some(gb(view.metrics.mem.total.max(view.metrics.mem.swap_total))),
+        ),
+        Panel::NetChart => ("Network", &[MetricKey::NetInBytes, MetricKey::NetOutBytes], None),
+        Panel::DiskChart => {
+            ("Disk", &[MetricKey::DiskReadKBytes, MetricKey::DiskWriteKBytes], None)
+        },
+        _ => ("Total Power", &[MetricKey::PackagePower], chip.tdp_w),
+    }
+}
+
+/// Renders the history of the series of `panel` over the chart window ending at the cursor.
+fn render_chart(view: &View, panel: Panel, frame: &mut Frame, area: Rect) {
+    let (title, keys, full_scale) = chart_spec(view, panel);
+    let time_axis = view.app.config.charts.time_axis;
+    let window = view.app.chart_window.seconds();
+    // Two braille dots per cell, long windows are summarized to what can be drawn.
+    let buckets: Vec<Vec<Bucket>> = keys
+        .iter()
+        .map(|&key| view.history.series(key).downsample(window, view.end, area.width as usize * 2))
+        .collect();
+    let means: Vec<Vec<(f64, f64)>> = buckets
+        .iter()
+        .map(|buckets| buckets.iter().map(|b| (b.time - view.end, b.mean)).collect())
+        .collect();
+    let theme = &view.app.theme;
+    let mut datasets: Vec<Dataset> = keys
+        .iter()
+        .zip(&means)
+        .enumerate()
+        .map(|(index, (&key, data))| {
+            // A single series takes the color of its panel, if it has one.
+            let color = match view.style(panel).fg {
+                Some(color) if keys.len() == 1 => Some(color),
+                _ => theme.series.get(index % theme.series.len().max(1)).and_then(|style| style.fg),
+            };
+            let marker = match color {
+                Some(_) => symbols::Marker::Braille,
+                None => MARKERS[index % MARKERS.len()],
+            };
+            let style = color.map_or_else(Style::default, |color| Style::default().fg(color));
+            let dataset = Dataset::default()
+                .marker(marker)
+                .graph_type(GraphType::Line)
+                .style(style)
+                .data(data);
+            match keys.len() {
+                1 => dataset,
+                _ => dataset.name(key.label()),
+            }
+        })
+        .collect();
+    // A single series also shows the range of each bucket, so short spikes stay visible.
+    let envelope: Vec<(f64, f64)> = match buckets.as_slice() {
+        [buckets] if buckets.iter().any(|b| b.min < b.max) => buckets
+            .iter()
+            .flat_map(|b| [(b.time - view.end, b.min), (b.time - view.end, b.max)])
+            .collect(),
+        _ => vec![],
+    };
+    if !envelope.is_empty() {
+        let dataset = Dataset::default()
+            .marker(symbols::Marker::Braille)
+            .graph_type(GraphType::Line)
+            .style(theme.muted)
+            .data(&envelope);
+        datasets.insert(0, dataset);
+    }
+    // Samples beyond the full scale, e.g. power above the typical TDP, still fit.
+    let y_max =
+        buckets.iter().flatten().map(|b| b.max).fold(full_scale.unwrap_or_default(), f64::max);
+    // Idle series still get a readable axis.
+    let y_max = if y_max > 0.0 { y_max } else { 1.0 };
+    let unit = keys[0].unit();
+    let values: Vec<String> = keys.iter().map(|&key| format_compact(view.value(key))).collect();
+    let label = view.app.chart_window.label();
+    let mut block =
+        Block::bordered().title(format!("{} {unit} {title} ({label})", values.join(" / ")));
+    if keys.len() == 1 {
+        block = block.title(view.stats_title(keys[0]));
+    }
+    let x_labels = time_labels(time_axis, view.end, window);
+    let y_labels: Vec<Span> =
+        vec!["0".into(), format_compact(y_max / 2.0).into(), format_compact(y_max).into()];
+    let inner = block.inner(area);
+    let plot = plot_area(inner, &x_labels, &y_labels);
+    let chart = Chart::new(datasets)
+        .block(block)
+        .hidden_legend_constraints((Constraint::Ratio(1, 2), Constraint::Ratio(1, 2)))
+        .x_axis(Axis::default().bounds([-window, 0.0]).labels(x_labels))
+        .y_axis(Axis::default().bounds([0.0, y_max]).labels(y_labels));
+    frame.render_widget(chart, area);
+
+    if let Some((column, row)) = view.app.pointer {
+        if plot.contains(Position { x: column, y: row }) {
+            let ago = window * (1.0 - (column - plot.x) as f64 / plot.width.max(1) as f64);
+            render_tooltip(view, keys, view.end - ago, (column, row), frame, inner);
+        }
+    }
+}
+
+/// Area of a chart drawn in `inner` where the data goes, right of the y-axis labels and above the
+/// x-axis labels, as laid out by [`Chart`].
+fn plot_area(inner: Rect, x_labels: &[Span], y_labels: &[Span]) -> Rect {
+    let y_width = y_labels.iter().map(Span::width).max().unwrap_or_default();
+    // The first x label ends under the y-axis.
+    let x_width = x_labels.first().map_or(0, |label| label.width().saturating_sub(1));
+    let left = inner.x + y_width.max(x_width) as u16 + 1;
+    Rect {
+        x: left,
+        y: inner.y,
+        width: inner.right().saturating_sub(left),
+        height: inner.height.saturating_sub(2),
+    }
+}
+
+/// Renders the values of `keys` at `time` in a box next to the mouse `pointer`, inside `area`.
+fn render_tooltip(
+    view: &View,
+    keys: &[MetricKey],
+    time: f64,
+    pointer: (u16, u16),
+    frame: &mut Frame,
+    area: Rect,
+) {
+    let when = match view.app.config.charts.time_axis {
+        TimeAxis::Relative => super::format_offset(view.end - time),
+        TimeAxis::Clock => format_clock(time),
+    };
+    let mut lines = vec![Line::from(Span::from(when).bold())];
+    for &key in keys {
+        let value = view.history.value_at(key, time).map_or("-".to_string(), format_compact);
+        lines.push(Line::from(format!("{}: {value} {}", key.label(), key.unit())));
+    }
+    let width = lines.iter().map(Line::width).max().unwrap_or_default() as u16 + 2;
+    let height = lines.len() as u16 + 2;
+    // Right of the pointer if it fits, else left of it.
+    let x = if pointer.0 + 2 + width <= area.right() {
+        pointer.0 + 2
+    } else {
+        pointer.0.saturating_sub(width + 1).max(area.x)
+    };
+    let y = pointer.1.min(area.bottom().saturating_sub(height)).max(area.y);
+    let tooltip = Rect { x, y, width, height }.intersection(area);
+    frame.render_widget(Clear, tooltip);
+    frame.render_widget(Paragraph::new(lines).block(Block::bordered()), tooltip);
+}
+
+/// Labels of the start, middle and end of a time axis ending at `end`.
+fn time_labels(axis: TimeAxis, end: f64, window: f64) -> Vec<Span<'static>> {
+    [window, window / 2.0, 0.0]
+        .iter()
+        .map(|&ago| match axis {
+            TimeAxis::Relative if ago == 0.0 => "now".to_string(),
+            TimeAxis::Relative => format!("-{}", format_span(ago)),
+            TimeAxis::Clock => format_clock(end - ago),
+        })
+        .map(Span::from)
+        .collect()
+}
+
+fn render_memory(view: &View, frame: &mut Frame, area: Rect) {
+    let mem = &view.metrics.mem;
+    frame.render_widget(
+        Gauge::default()
+            .block(
+                Block::bordered()
+                    .title(format!(
+                        "Memory Usage: {:.2} GB / {:.2} GB (Swap: {:.2}/{:.2} GB)",
+                        mem.used as f64 / 1024.0 / 1024.0 / 1024.0,
+                        mem.total as f64 / 1024.0 / 1024.0 / 1024.0,
+                        mem.swap_used as f64 / 1024.0 / 1024.0 / 1024.0,
+                        mem.swap_total as f64 / 1024.0 / 1024.0 / 1024.0,
+                    ))
+                    .title(view.stats_title(MetricKey::MemoryUsed)),
+            )
+            .gauge_style(view.style(Panel::Memory))
+            .percent((mem.used as f64 * 100.0 / mem.total.max(1) as f64).clamp(0.0, 100.0) as u16),
+        area,
+    );
+}
+
+fn render_processes(view: &View, frame: &mut Frame, area: Rect) {
+    let app = view.app;
+    let processes = app.processes(view.metrics);
+    let header = Row::new(["PID", "Name", "CPU %", "Energy impact"]).bold();
+    // Keep the last process in view when the list shrinks.
+    let offset = app.process_offset.min(processes.len().saturating_sub(1));
+    let rows = processes.iter().skip(offset).map(|process| {
+        Row::new([
+            process.pid.to_string(),
+            process.name.clone(),
+            format!("{:.1}", process.cpu_percent()),
+            format!("{:.1}", process.energy_impact),
+        ])
+    });
+    let table = Table::new(
+        rows,
+        [Constraint::Length(7), Constraint::Min(16), Constraint::Length(7), Constraint::Length(13)],
+    )
+    .style(view.style(Panel::Processes))
+    .header(header)
+    .block(Block::bordered().title(process_title(app, processes.len(), offset)));
+    frame.render_widget(table, area);
+}
+
+/// `Processes (12)`, with the first one shown, the sort order and the filter if not the defaults.
+fn process_title(app: &App, count: usize, offset: usize) -> String {
+    let mut title = format!("Processes ({count}");
+    if offset > 0 {
+        title.push_str(&format!(", from #{}", offset + 1));
+    }
+    if app.process_sort != ProcessSort::default() {
+        title.push_str(&format!(", by {}", app.process_sort.label()));
+    }
+    if !app.process_filter.is_empty() {
+        title.push_str(&format!(", matching `{}`", app.process_filter));
+    }
+    title.push(')');
+    title
+}
+
+/// Energy, cost and emissions per rail, then per process.
+pub fn energy_table(energy: &EnergyMeter, tariff: &TariffConfig) -> Table<'static> {
+    let header = Row::new(["", "Energy", "Wh", "Cost", "CO2"]).bold();
+    let row = |name: &str, joules: f64| {
+        Row::new([
+            name.to_string(),
+            format!("{joules:.1} J"),
+            format!("{:.4}", joules / 3600.0),
+            format!("{}{:.4}", tariff.currency, tariff.cost(joules)),
+            format!("{:.1} g", tariff.emissions_g(joules)),
+        ])
+    };
+    let rails = Rail::ALL.iter().map(|&rail| row(rail.label(), energy.joules(rail)).bold());
+    let processes = energy.processes().into_iter().map(|(name, joules)| row(name, joules));
+    Table::new(
+        rails.chain(processes).collect::<Vec<_>>(),
+        [
+            Constraint::Min(20),
+            Constraint::Length(14),
+            Constraint::Length(10),
+            Constraint::Length(12),
+            Constraint::Length(10),
+        ],
+    )
+    .header(header)
+}
+
+fn render_energy(view: &View, frame: &mut Frame, area: Rect) {
+    let energy = &view.energy;
+    let table = energy_table(energy, &view.app.config.tariff)
+        .style(view.style(Panel::Energy))
+        .block(Block::bordered().title(format!(
+            "Energy over {}, processes estimated from energy impact",
+            format_duration(energy.elapsed())
+        )));
+    frame.render_widget(table, area);
+}
+
+fn render_top_consumers(view: &View, frame: &mut Frame, area: Rect) {
+    let consumers = top_consumers(view.history, view.end - view.window.seconds(), view.end);
+    let total: f64 = consumers.iter().map(|consumer| consumer.joules).sum();
+    let header = Row::new(["Name", "Energy", "Share", "Impact"]).bold();
+    let rows = consumers.iter().map(|consumer| {
+        let row = Row::new([
+            if consumer.running {
+                consumer.name.clone()
+            } else {
+                format!("{} (exited)", consumer.name)
+            },
+            format!("{:.1} J", consumer.joules),
+            format!("{:.1}%", consumer.joules * 100.0 / total.max(f64::MIN_POSITIVE)),
+            format!("{:.1}", consumer.energy_impact),
+        ]);
+        if consumer.running {
+            row
+        } else {
+            row.style(view.app.theme.muted)
+        }
+    });
+    let table = Table::new(
+        rows,
+        [Constraint::Min(16), Constraint::Length(10), Constraint::Length(7), Constraint::Length(9)],
+    )
+    .style(view.style(Panel::TopConsumers))
+    .header(header)
+    .block(
+        Block::bordered().title(format!(
+            "Top consumers over {}, estimated from energy impact",
+            view.window.label()
+        )),
+    );
+    frame.render_widget(table, area);
+}
+
+/// Active alerts at `time`, most severe first, with how long they have been active.
+pub fn alert_table(alerts: &Alerts, time: f64, theme: &Theme) -> Table<'static> {
+    let header = Row::new(["Severity", "Alert", "Value", "Active for"]).bold();
+    let rows: Vec<Row> = alerts
+        .active()
+        .into_iter()
+        .map(|alert| {
+            Row::new([
+                Cell::from(alert.severity.label()).style(severity_style(alert.severity, theme)),
+                Cell::from(alert.title.clone()),
+                Cell::from(alert.value.clone()),
+                Cell::from(format_duration(time - alert.since)),
+            ])
+        })
+        .collect();
+    Table::new(
+        rows,
+        [
+            Constraint::Length(8),
+            Constraint::Min(20),
+            Constraint::Length(14),
+            Constraint::Length(10),
+        ],
+    )
+    .header(header)
+}
+
+fn render_alerts(view: &View, frame: &mut Frame, area: Rect) {
+    let count = view.alerts.active().len();
+    let table = alert_table(&view.alerts, view.end, &view.app.theme)
+        .style(view.style(Panel::Alerts))
+        .block(Block::bordered().title(format!("Alerts ({count})")));
+    frame.render_widget(table, area);
+}
+
+fn render_cpu_clusters(view: &View, frame: &mut Frame, area: Rect) {
+    let cpu_metrics = &view.metrics.cpu_metrics;
+    let clusters = [
+        ("E0", cpu_metrics.e0_cluster_active, cpu_metrics.e0_cluster_freq_mhz),
+        ("E1", cpu_metrics.e1_cluster_active, cpu_metrics.e1_cluster_freq_mhz),
+        ("P0", cpu_metrics.p0_cluster_active, cpu_metrics.p0_cluster_freq_mhz),
+        ("P1", cpu_metrics.p1_cluster_active, cpu_metrics.p1_cluster_freq_mhz),
+        ("P2", cpu_metrics.p2_cluster_active, cpu_metrics.p2_cluster_freq_mhz),
+    ];
+    // Chips have one to five clusters, the missing ones never run.
+    let items = clusters
+        .iter()
+        .filter(|&&(_, active, freq_mhz)| active > 0 || freq_mhz > 0)
+        .map(|(name, active, freq_mhz)| format!("{name}-Cluster: {active}% @ {freq_mhz} MHz"));
+    let list = List::new(items).style(view.style(Panel::CpuClusters)).block(
+        Block::bordered().title(format!("Clusters · CPU Power: {:.2} W", cpu_metrics.cpu_w)),
+    );
+    frame.render_widget(list, area);
+}
+
+fn render_cpu_cores(view: &View, frame: &mut Frame, area: Rect) {
+    let cpu_metrics = &view.metrics.cpu_metrics;
+    let block = Block::bordered().title(format!("Cores ({})", cpu_metrics.cores.len()));
+    let inner = block.inner(area);
+    frame.render_widget(block, area);
+    if cpu_metrics.cores.is_empty() || inner.height == 0 {
+        return;
+    }
+    let e_cores = view.metrics.soc_info.e_core_count as usize;
+    // As many columns as needed to fit every core, one core per line.
+    let columns = cpu_metrics.cores.len().div_ceil(inner.height as usize);
+    let column_areas = Layout::default()
+        .direction(Direction::Horizontal)
+        .constraints(vec![Constraint::Ratio(1, columns as u32); columns])
+        .split(inner);
+    let rows = inner.height as usize;
+    for (index, &active) in cpu_metrics.cores.iter().enumerate() {
+        let column = column_areas[index / rows];
+        let line = Rect { y: column.y + (index % rows) as u16, height: 1, ..column };
+        // E and P cores take the colors of their cluster gauges.
+        let (kind, panel) = if index < e_cores { ("E", Panel::ECpu) } else { ("P", Panel::PCpu) };
+        let freq_mhz = cpu_metrics.core_freqs_mhz.get(index).copied().unwrap_or_default();
+        let label = format!("{index:>2} {kind} {freq_mhz:>4} MHz {active:>3}% ");
+        let width = (line.width as usize).saturating_sub(label.len() + 1);
+        let filled = (width as f64 * active.clamp(0, 100) as f64 / 100.0).round() as usize;
+        let bar = Line::from(vec![
+            Span::from(label),
+            Span::styled(
+                "█".repeat(filled),
+                Style::default().fg(view.style(panel).fg.unwrap_or_default()),
+            ),
+            Span::styled("░".repeat(width - filled), view.app.theme.muted),
+        ]);
+        frame.render_widget(Paragraph::new(bar), line);
+    }
+}
+
+fn render_gpu_info(view: &View, frame: &mut Frame, area: Rect) {
+    let gpu_metrics = &view.metrics.gpu_metrics;
+    let cores = view.metrics.soc_info.gpu_core_count.or(view.chip.gpu_cores);
+    let cores = cores.map_or("?".to_string(), |cores| cores.to_string());
+    let items = [
+        format!("Active: {:.1}%", gpu_metrics.active),
+        format!("Frequency: {} MHz", gpu_metrics.freq_mhz),
+        format!("Power: {:.2} W", view.value(MetricKey::GpuPower)),
+        format!("Cores: {cores}"),
+    ];
+    let list =
+        List::new(items).style(view.style(Panel::GpuInfo)).block(Block::bordered().title("GPU"));
+    frame.render_widget(list, area);
+}
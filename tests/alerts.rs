@@ -0,0 +1,85 @@
+//! Evaluates alert rules on snapshots.
+
+use mactop_rs::{alert::Alerts, config::Config, host::Host, metrics::Metrics};
+
+/// A snapshot at `time` drawing `package_w`.
+fn sample(time: f64, package_w: f64) -> Metrics {
+    let mut metrics = Metrics { timestamp: time, ..Default::default() };
+    metrics.cpu_metrics.package_w = package_w;
+    metrics
+}
+
+#[test]
+fn alerts_at_the_cursor() {
+    let config = Config::parse("[[alerts.rules]]\nwhen = \"package_w > 25 for 2s\"").unwrap();
+    let mut host = Host { alerts: Alerts::new(&config.alerts.rules), ..Host::local(100) };
+    // Over the threshold from 10s to 19s.
+    for time in 0..30 {
+        let package_w = if (10..20).contains(&time) { 40.0 } else { 5.0 };
+        host.on_metrics(sample(time as f64, package_w));
+    }
+    assert!(host.alerts.active().is_empty());
+    // 29 - 15 = 14s back, firing since 12s.
+    let alerts = host.alerts_at(14);
+    assert_eq!(alerts.active().len(), 1);
+    assert_eq!(alerts.active()[0].since, 12.0);
+    assert!(host.alerts_at(9).active().is_empty());
+    assert!(host.alerts_at(18).active().is_empty());
+}
+
+#[test]
+fn replayed_alerts_are_cached_until_the_next_snapshot() {
+    let config = Config::parse("[[alerts.rules]]\nwhen = \"package_w > 25\"").unwrap();
+    let mut host = Host { alerts: Alerts::new(&config.alerts.rules), ..Host::local(100) };
+    for time in 0..10 {
+        host.on_metrics(sample(time as f64, if time == 5 { 40.0 } else { 5.0 }));
+    }
+    assert_eq!(host.alerts_at(4).active().len(), 1);
+    assert!(matches!(*host.replayed_alerts.borrow(), Some((4, _))));
+    assert_eq!(host.alerts_at(4).active().len(), 1);
+
+    // The same offset is another snapshot once a new one comes.
+    host.on_metrics(sample(10.0, 5.0));
+    assert!(host.replayed_alerts.borrow().is_none());
+    assert!(host.alerts_at(4).active().is_empty());
+    assert_eq!(host.alerts_at(5).active().len(), 1);
+}
+
+#[test]
+fn equality_alerts_clear_after_their_duration() {
+    let config = Config::parse("[[alerts.rules]]\nwhen = \"thermal == heavy for 10s\"").unwrap();
+    let mut alerts = Alerts::new(&config.alerts.rules);
+    let thermal = |time: f64, level: &str| Metrics {
+        timestamp: time,
+        thermal_pressure: level.to_string(),
+        ..Default::default()
+    };
+    for time in 0..=10 {
+        alerts.update(&thermal(time as f64, "Heavy"));
+    }
+    assert_eq!(alerts.active().len(), 1);
+    // Flapping at the boundary keeps it active.
+    for (time, level) in
+        [(11.0, "Moderate"), (12.0, "Heavy"), (13.0, "Moderate"), (20.0, "Moderate")]
+    {
+        assert!(alerts.update(&thermal(time, level)).is_empty(), "{time}");
+    }
+    let changes = alerts.update(&thermal(23.0, "Moderate"));
+    assert_eq!(changes.len(), 1);
+    assert!(alerts.active().is_empty());
+}
+
+#[test]
+fn equality_rules_take_no_hysteresis() {
+    let config = Config::parse(
+        "[[alerts.rules]]\nwhen = \"thermal != nominal\"\nhysteresis = 1\n\n[[alerts.rules]]\nwhen = \
+         \"package_w > 25\"\nhysteresis = 2",
+    )
+    .unwrap();
+    let err = config.alerts.validate().unwrap_err();
+    assert!(err.contains("thermal != nominal"), "{err}");
+
+    let config =
+        Config::parse("[[alerts.rules]]\nwhen = \"package_w > 25\"\nhysteresis = 2").unwrap();
+    assert!(config.alerts.validate().is_ok());
+}
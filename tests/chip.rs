@@ -0,0 +1,29 @@
+//! Looks chips up in the built-in database, falling back to the config.
+
+use mactop_rs::{chip::ChipSpec, config::Config};
+
+#[test]
+fn lookup_by_brand_string() {
+    let pro = ChipSpec::lookup("Apple M1 Pro").unwrap();
+    assert_eq!((pro.gpu_cores, pro.p_freq_mhz), (Some(16), Some(3228)));
+    assert_eq!(ChipSpec::lookup("Apple M1").unwrap().gpu_cores, Some(8));
+    assert_eq!(ChipSpec::lookup("Apple M2 Ultra").unwrap().ane_peak_w, Some(16.0));
+    // Asahi Linux only names the board.
+    assert_eq!(
+        ChipSpec::lookup("Apple MacBook Pro (14-inch, M1 Max, 2021)").unwrap().gpu_cores,
+        Some(32)
+    );
+    assert_eq!(ChipSpec::lookup("Intel(R) Core(TM) i7-9750H CPU @ 2.60GHz"), None);
+    assert_eq!(ChipSpec::lookup("Apple M10"), None);
+}
+
+#[test]
+fn config_only_applies_to_unknown_chips() {
+    let config = Config::parse("[chip]\ntdp_w = 30.0\ngpu_cores = 7").unwrap();
+    let chip = config.chip.resolve("Apple M9");
+    assert_eq!(chip, ChipSpec { tdp_w: Some(30.0), gpu_cores: Some(7), ..Default::default() });
+
+    // Another host of the same session keeps the figures of its chip.
+    assert_eq!(config.chip.resolve("Apple M1"), ChipSpec::lookup("Apple M1").unwrap());
+    assert!(Config::parse("[chip]\nmax_freq = 3000").is_err());
+}
@@ -0,0 +1,99 @@
+//! Renders the dashboard of several hosts and moves between it and a single host.
+
+use std::{fs, path::PathBuf};
+
+use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+use mactop_rs::{
+    app::App, config::Config, handler::handle_key_events, metrics::Metrics,
+    remote::ConnectionState, ui,
+};
+use ratatui::{backend::TestBackend, Terminal};
+
+fn fixture(name: &str) -> Metrics {
+    let path = PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+        .join("tests/fixtures/metrics")
+        .join(format!("{name}.json"));
+    serde_json::from_str(&fs::read_to_string(path).unwrap()).unwrap()
+}
+
+/// A dashboard of three agents: a loaded M2 Max, an idle M1 and one never reached.
+fn app() -> App {
+    let addrs = ["studio:7878", "air:7878", "mini:7878"].map(String::from).to_vec();
+    let mut app = App::remote(addrs, Config::default());
+    for (index, name) in [(0, "loaded_m2_max"), (1, "idle_m1")] {
+        app.on_connection(index, ConnectionState::Connected);
+        app.on_metrics(index, fixture(name));
+    }
+    app.on_connection(2, ConnectionState::Lost("connection refused".to_string()));
+    app
+}
+
+fn press(app: &mut App, code: KeyCode) {
+    handle_key_events(KeyEvent::new(code, KeyModifiers::NONE), app).unwrap();
+}
+
+/// The rendered lines, without trailing spaces.
+fn render(app: &mut App) -> Vec<String> {
+    let mut terminal = Terminal::new(TestBackend::new(140, 20)).unwrap();
+    terminal.draw(|frame| ui::render(app, frame)).unwrap();
+    let buffer = terminal.backend().buffer();
+    (0..20)
+        .map(|y| (0..140).map(|x| buffer.get(x, y).symbol()).collect::<String>())
+        .map(|line| line.trim_end().to_string())
+        .collect()
+}
+
+/// The words of the row of `host`.
+fn row(lines: &[String], host: &str) -> Vec<String> {
+    let line = lines
+        .iter()
+        .find(|line| line.contains(host))
+        .unwrap_or_else(|| panic!("no row for {host} in\n{}", lines.join("\n")));
+    line.trim_matches(|c: char| c == '│' || c.is_whitespace())
+        .split("  ")
+        .map(str::trim)
+        .filter(|cell| !cell.is_empty())
+        .map(String::from)
+        .collect()
+}
+
+#[test]
+fn rows_summarize_each_host() {
+    let mut app = app();
+    assert!(app.is_dashboard());
+    let lines = render(&mut app);
+    // Memory is 55297703936 of 68719476736 bytes.
+    let expected = ["studio:7878", "Apple M2 Max", "96%", "88%", "92%", "51.35 W", "80%"];
+    assert_eq!(row(&lines, "studio:7878")[..7], expected);
+    assert_eq!(row(&lines, "studio:7878")[7], "connected");
+    assert_eq!(row(&lines, "air:7878")[1], "Apple M1");
+}
+
+#[test]
+fn unreachable_host_shows_lost() {
+    let mut app = app();
+    let lines = render(&mut app);
+    let row = row(&lines, "mini:7878");
+    assert_eq!(row[1..7], ["-"; 6]);
+    assert_eq!(row[7], "reconnecting (connection refused)");
+}
+
+#[test]
+fn enter_drills_down_and_back_returns() {
+    let mut app = app();
+    press(&mut app, KeyCode::Down);
+    assert_eq!(app.selected, 1);
+    press(&mut app, KeyCode::Enter);
+    assert!(!app.is_dashboard());
+    assert!(app.can_go_back());
+    let lines = render(&mut app);
+    assert!(lines[0].contains("remote host: air:7878"), "{}", lines[0]);
+    assert!(lines.iter().all(|line| !line.contains("studio:7878")));
+
+    press(&mut app, KeyCode::Esc);
+    assert!(app.is_dashboard());
+    assert!(app.running);
+    let lines = render(&mut app);
+    assert!(lines.iter().any(|line| line.contains("studio:7878")));
+    assert!(lines.iter().any(|line| line.contains("3 hosts")));
+}
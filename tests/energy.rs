@@ -0,0 +1,89 @@
+//! Ranks processes by the energy they used over a window of the history.
+
+use mactop_rs::{
+    energy::{top_consumers, EnergyMeter, Rail},
+    history::{History, MetricKey},
+    metrics::{Metrics, ProcessMetrics},
+};
+
+/// A sample at `time` drawing `package_w`, with the energy impact of every process.
+fn sample(time: f64, package_w: f64, processes: &[(&str, f64)]) -> Metrics {
+    let mut metrics = Metrics { timestamp: time, ..Default::default() };
+    metrics.cpu_metrics.package_w = package_w;
+    metrics.processes = processes
+        .iter()
+        .map(|&(name, energy_impact)| ProcessMetrics {
+            name: name.to_string(),
+            energy_impact,
+            ..Default::default()
+        })
+        .collect();
+    metrics
+}
+
+fn history() -> History {
+    let mut history = History::new(100);
+    for time in 0..60 {
+        let time = time as f64;
+        // A short burst of `cc` around 50s, which exits before the end.
+        let processes: &[(&str, f64)] = match time {
+            t if (48.0..52.0).contains(&t) => &[("cc", 90.0), ("node", 10.0)],
+            t if t < 20.0 => &[("backup", 50.0), ("node", 10.0)],
+            _ => &[("node", 10.0)],
+        };
+        let package_w = if (48.0..52.0).contains(&time) { 80.0 } else { 5.0 };
+        history.record(&sample(time, package_w, processes));
+    }
+    history
+}
+
+#[test]
+fn exited_burst_is_ranked_first() {
+    let history = history();
+    let consumers = top_consumers(&history, 29.0, 59.0);
+    let names: Vec<&str> = consumers.iter().map(|consumer| consumer.name.as_str()).collect();
+    assert_eq!(names, ["cc", "node"]);
+    assert!(!consumers[0].running && consumers[1].running);
+    assert_eq!(consumers[0].energy_impact, 4.0 * 90.0);
+    // The package energy of the window is shared between both.
+    let total: f64 = consumers.iter().map(|consumer| consumer.joules).sum();
+    let package = history.series(MetricKey::PackagePower).integrate(29.0, 59.0);
+    assert!((total - package).abs() < 1e-9);
+}
+
+#[test]
+fn window_leaves_out_older_processes() {
+    let history = history();
+    assert!(top_consumers(&history, 29.0, 59.0).iter().all(|consumer| consumer.name != "backup"));
+    // Running as of the end of the window, not of the history.
+    let early = top_consumers(&history, 0.0, 19.0);
+    assert_eq!(early[0].name, "backup");
+    assert!(early[0].running);
+    assert!(top_consumers(&History::new(10), 0.0, 60.0).is_empty());
+}
+
+#[test]
+fn meter_rewinds_to_the_cursor() {
+    let history = history();
+    let (mut meter, mut until_40) = (EnergyMeter::default(), EnergyMeter::default());
+    for back in (0..history.len()).rev() {
+        let metrics = history.snapshot(back).unwrap();
+        meter.record(metrics);
+        if metrics.timestamp <= 40.0 {
+            until_40.record(metrics);
+        }
+    }
+    let rewound = meter.at(&history, 40.0);
+    assert_eq!(rewound.elapsed(), 40.0);
+    for rail in Rail::ALL {
+        assert!((rewound.joules(rail) - until_40.joules(rail)).abs() < 1e-9);
+    }
+    // `cc` only ran after the cursor.
+    let (processes, expected) = (rewound.processes(), until_40.processes());
+    assert_eq!(processes.len(), expected.len());
+    for ((name, joules), (expected_name, expected_joules)) in processes.into_iter().zip(expected) {
+        assert_eq!(name, expected_name);
+        assert!((joules - expected_joules).abs() < 1e-9);
+    }
+    assert_eq!(meter.at(&history, 59.0).joules(Rail::Package), meter.joules(Rail::Package));
+}
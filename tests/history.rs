@@ -0,0 +1,95 @@
+//! Keeps the newest samples of every metric and looks them up by time.
+
+use mactop_rs::{
+    history::{History, MetricKey, RingBuffer, Sample, Series},
+    metrics::{Metrics, ProcessMetrics},
+};
+
+#[test]
+fn ring_buffer_drops_the_oldest_at_capacity() {
+    let mut buffer = RingBuffer::new(3);
+    assert!(buffer.is_empty() && buffer.first().is_none());
+    for item in 1..=5 {
+        buffer.push(item);
+    }
+    assert_eq!((buffer.len(), buffer.capacity()), (3, 3));
+    assert_eq!(buffer.iter().copied().collect::<Vec<_>>(), [3, 4, 5]);
+    assert_eq!(
+        (buffer.first(), buffer.last(), buffer.get(1), buffer.get(3)),
+        (Some(&3), Some(&5), Some(&4), None)
+    );
+
+    // A zero capacity still keeps the newest element.
+    let mut buffer = RingBuffer::new(0);
+    buffer.push(1);
+    buffer.push(2);
+    assert_eq!(buffer.iter().copied().collect::<Vec<_>>(), [2]);
+}
+
+/// Samples of value `time * 10` every second from 0 to 9.
+fn series() -> Series {
+    let mut series = Series::new(100);
+    for time in 0..10 {
+        series.push(Sample { time: time as f64, value: time as f64 * 10.0 });
+    }
+    series
+}
+
+#[test]
+fn series_time_queries() {
+    let series = series();
+    let times = |samples: Vec<&Sample>| samples.iter().map(|s| s.time).collect::<Vec<_>>();
+    assert_eq!(times(series.since(7.0).collect()), [7.0, 8.0, 9.0]);
+    assert_eq!(times(series.until(2.0).collect()), [0.0, 1.0, 2.0]);
+    assert_eq!(times(series.tail(2, 5.5).collect()), [4.0, 5.0]);
+    assert_eq!(times(series.tail(5, 1.0).collect()), [0.0, 1.0]);
+
+    // The newest sample at or before the time.
+    assert_eq!(series.value_at(4.5), Some(40.0));
+    assert_eq!(series.value_at(4.0), Some(40.0));
+    assert_eq!(series.value_at(-1.0), None);
+    assert_eq!(series.value_at(100.0), Some(90.0));
+    assert_eq!(series.latest(), Some(90.0));
+
+    // Trapezoids of the samples within the range, 10 * t integrated from 2 to 4.
+    assert_eq!(series.integrate(2.0, 4.0), 60.0);
+    assert_eq!(series.integrate(20.0, 30.0), 0.0);
+}
+
+fn metrics(time: f64, package_w: f64, processes: &[&str]) -> Metrics {
+    let mut metrics = Metrics { timestamp: time, ..Default::default() };
+    metrics.cpu_metrics.package_w = package_w;
+    metrics.processes = processes
+        .iter()
+        .map(|&name| ProcessMetrics {
+            name: name.to_string(),
+            energy_impact: 1.0,
+            ..Default::default()
+        })
+        .collect();
+    metrics
+}
+
+#[test]
+fn history_wraps_around_at_retention() {
+    let mut history = History::new(5);
+    for time in 0..8 {
+        // `early` exits after the third sample.
+        let processes: &[&str] = if time < 3 { &["early", "late"] } else { &["late"] };
+        history.record(&metrics(time as f64, time as f64, processes));
+    }
+    assert_eq!((history.len(), history.retention()), (5, 5));
+    let power = history.series(MetricKey::PackagePower);
+    assert_eq!((power.first().unwrap().time, power.len()), (3.0, 5));
+    assert_eq!(history.latest(MetricKey::PackagePower), Some(7.0));
+    assert_eq!(history.value_at(MetricKey::PackagePower, 3.5), Some(3.0));
+
+    // Snapshots counted back from the newest.
+    assert_eq!(history.snapshot(0).unwrap().timestamp, 7.0);
+    assert_eq!(history.snapshot(4).unwrap().timestamp, 3.0);
+    assert!(history.snapshot(5).is_none());
+
+    // Exited processes are forgotten once their samples leave the retention window.
+    assert!(history.processes().contains_key("late"));
+    assert!(!history.processes().contains_key("early"));
+}
@@ -0,0 +1,196 @@
+use std::{
+    fs,
+    io::{BufRead, BufReader, Read, Write},
+    net::TcpListener,
+    sync::mpsc,
+    thread,
+    time::{Duration, Instant},
+};
+
+use mactop_rs::{
+    config::Config,
+    hook::{post_webhook, run_command, Hooks, Notification, RateLimit, Webhook},
+};
+
+fn notification() -> Notification {
+    Notification {
+        host: "localhost".to_string(),
+        state: "fired".to_string(),
+        title: "Hot package".to_string(),
+        rule: "package_w > 25 for 10s".to_string(),
+        severity: "critical".to_string(),
+        value: "27.50 W".to_string(),
+        time: 1700000000.0,
+    }
+}
+
+/// A request received by [`serve_once`].
+struct Request {
+    head: String,
+    body: String,
+}
+
+/// Listens on a free local port, answers the first request with `status` and sends it back.
+fn serve_once(status: &'static str) -> (Webhook, mpsc::Receiver<Request>) {
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let port = listener.local_addr().unwrap().port();
+    let (tx, rx) = mpsc::channel();
+    thread::spawn(move || {
+        let (stream, _) = listener.accept().unwrap();
+        let mut reader = BufReader::new(stream);
+        let mut head = String::new();
+        loop {
+            let mut line = String::new();
+            reader.read_line(&mut line).unwrap();
+            if line == "\r\n" || line.is_empty() {
+                break;
+            }
+            head.push_str(&line);
+        }
+        let length = head
+            .lines()
+            .find_map(|line| line.strip_prefix("Content-Length: "))
+            .map_or(0, |length| length.trim().parse().unwrap());
+        let mut body = vec![0; length];
+        reader.read_exact(&mut body).unwrap();
+        write!(reader.get_mut(), "HTTP/1.1 {status}\r\nContent-Length: 0\r\n\r\n").unwrap();
+        tx.send(Request { head, body: String::from_utf8(body).unwrap() }).unwrap();
+    });
+    let webhook = Webhook::try_from(format!("http://127.0.0.1:{port}/hooks/mactop?x=1")).unwrap();
+    (webhook, rx)
+}
+
+#[test]
+fn webhook_posts_the_alert_as_json() {
+    let (webhook, requests) = serve_once("200 OK");
+    post_webhook(&webhook, &notification(), Duration::from_secs(5)).unwrap();
+
+    let request = requests.recv_timeout(Duration::from_secs(5)).unwrap();
+    assert!(request.head.starts_with("POST /hooks/mactop?x=1 HTTP/1.1\r\n"), "{}", request.head);
+    assert!(request.head.contains("Content-Type: application/json\r\n"));
+    let received: Notification = serde_json::from_str(&request.body).unwrap();
+    assert_eq!(received, notification());
+}
+
+#[test]
+fn webhook_fails_on_error_status() {
+    let (webhook, _requests) = serve_once("500 Internal Server Error");
+    let err = post_webhook(&webhook, &notification(), Duration::from_secs(5)).unwrap_err();
+    assert!(err.to_string().contains("answered 500"), "{err}");
+}
+
+#[test]
+fn webhook_times_out_when_nothing_answers() {
+    // Accepts the connection but never answers.
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let port = listener.local_addr().unwrap().port();
+    let webhook = Webhook::try_from(format!("http://127.0.0.1:{port}")).unwrap();
+    let start = Instant::now();
+    assert!(post_webhook(&webhook, &notification(), Duration::from_millis(200)).is_err());
+    assert!(start.elapsed() < Duration::from_secs(5));
+    drop(listener);
+}
+
+#[test]
+fn webhook_urls() {
+    let webhook = Webhook::try_from("http://example.com".to_string()).unwrap();
+    assert_eq!(
+        (webhook.host.as_str(), webhook.port, webhook.path.as_str()),
+        ("example.com", 80, "/")
+    );
+    assert!(Webhook::try_from("https://example.com/".to_string()).is_err());
+    assert!(Webhook::try_from("http://example.com:http/".to_string()).is_err());
+    assert!(Webhook::try_from("http:///path".to_string()).is_err());
+}
+
+#[test]
+fn command_gets_the_alert_in_its_environment() {
+    let path = std::env::temp_dir().join(format!("mactop-rs-hook-{}", std::process::id()));
+    let command = format!(
+        "printf '%s|%s|%s' \"$MACTOP_ALERT_TITLE\" \"$MACTOP_ALERT_STATE\" \
+         \"$MACTOP_ALERT_VALUE\" > '{}'",
+        path.display()
+    );
+    run_command(&command, &notification(), Duration::from_secs(5)).unwrap();
+    assert_eq!(fs::read_to_string(&path).unwrap(), "Hot package|fired|27.50 W");
+    fs::remove_file(path).unwrap();
+}
+
+#[test]
+fn command_failures_and_timeouts() {
+    assert!(run_command("exit 3", &notification(), Duration::from_secs(5)).is_err());
+
+    let start = Instant::now();
+    let err = run_command("sleep 10", &notification(), Duration::from_millis(100)).unwrap_err();
+    assert!(err.to_string().contains("timed out"), "{err}");
+    assert!(start.elapsed() < Duration::from_secs(5));
+}
+
+#[test]
+fn rate_limit_per_key() {
+    let mut limit = RateLimit::new(60.0);
+    assert!(limit.allow("a", 0.0));
+    assert!(!limit.allow("a", 30.0));
+    assert!(limit.allow("b", 30.0));
+    assert!(limit.allow("a", 60.0));
+    assert!(!limit.allow("a", 119.0));
+}
+
+#[test]
+fn rules_with_the_same_condition_are_limited_apart() {
+    let dir = std::env::temp_dir().join(format!("mactop-rs-hooks-{}", std::process::id()));
+    let _ = fs::remove_dir_all(&dir);
+    fs::create_dir_all(&dir).unwrap();
+    let config = Config::parse(&format!(
+        "[[alerts.rules]]\nwhen = \"package_w > 25\"\ncommand = \"touch '{0}/first'\"\n\n\
+         [[alerts.rules]]\nwhen = \"package_w > 25\"\ncommand = \"touch '{0}/second'\"",
+        dir.display()
+    ))
+    .unwrap();
+    let mut hooks = Hooks::new(&config.alerts.hooks);
+    for (index, rule) in config.alerts.rules.iter().enumerate() {
+        hooks.notify(notification(), index, rule);
+    }
+
+    let start = Instant::now();
+    while !(dir.join("first").exists() && dir.join("second").exists()) {
+        assert!(start.elapsed() < Duration::from_secs(5), "a rule's hook didn't run");
+        thread::sleep(Duration::from_millis(20));
+    }
+    fs::remove_dir_all(dir).unwrap();
+}
+
+#[test]
+fn hooks_timeout_must_be_positive() {
+    for timeout in ["0", "-1", "1e20", "inf", "nan"] {
+        let config = Config::parse(&format!("[alerts.hooks]\ntimeout = {timeout}")).unwrap();
+        let err = config.alerts.validate().unwrap_err();
+        assert!(err.contains("timeout"), "{err}");
+    }
+    let config = Config::parse("[alerts.hooks]\ntimeout = 0.5").unwrap();
+    assert!(config.alerts.validate().is_ok());
+}
+
+#[test]
+fn hooks_config() {
+    let config = Config::parse(
+        r#"
+        [alerts.hooks]
+        command = "say alert"
+        webhook = "http://localhost:8080/alerts"
+        timeout = 2
+
+        [[alerts.rules]]
+        when = "package_w > 25"
+        command = "pkill -f build-agent"
+        "#,
+    )
+    .unwrap();
+    let hooks = &config.alerts.hooks;
+    assert_eq!(hooks.command.as_deref(), Some("say alert"));
+    assert_eq!(hooks.webhook.as_ref().unwrap().port, 8080);
+    assert_eq!((hooks.timeout, hooks.min_interval), (2.0, 60.0));
+    assert_eq!(config.alerts.rules[0].command.as_deref(), Some("pkill -f build-agent"));
+
+    assert!(Config::parse("[alerts.hooks]\nwebhook = \"https://example.com\"").is_err());
+}
@@ -0,0 +1,167 @@
+//! Key bindings, their config and the actions they perform.
+
+use std::{env, fs};
+
+use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+use mactop_rs::{
+    app::{App, ProcessSort},
+    config::Config,
+    handler::handle_key_events,
+    keymap::{Action, KeyBinding, Keymap},
+    metrics::{Metrics, ProcessMetrics},
+};
+
+fn key(code: KeyCode) -> KeyEvent {
+    KeyEvent::new(code, KeyModifiers::NONE)
+}
+
+fn press(app: &mut App, text: &str) {
+    for char in text.chars() {
+        handle_key_events(key(KeyCode::Char(char)), app).unwrap();
+    }
+}
+
+#[test]
+fn parse_and_display() {
+    for (text, code, modifiers) in [
+        ("q", KeyCode::Char('q'), KeyModifiers::NONE),
+        ("-", KeyCode::Char('-'), KeyModifiers::NONE),
+        ("space", KeyCode::Char(' '), KeyModifiers::NONE),
+        ("ctrl-d", KeyCode::Char('d'), KeyModifiers::CONTROL),
+        ("ctrl-alt-left", KeyCode::Left, KeyModifiers::CONTROL | KeyModifiers::ALT),
+        ("shift-left", KeyCode::Left, KeyModifiers::SHIFT),
+        ("f5", KeyCode::F(5), KeyModifiers::NONE),
+        ("PageDown", KeyCode::PageDown, KeyModifiers::NONE),
+    ] {
+        let binding: KeyBinding = text.parse().unwrap();
+        assert_eq!((binding.code, binding.modifiers), (code, modifiers), "{text}");
+        assert_eq!(binding.to_string(), text.to_lowercase());
+    }
+    // What terminals report for Shift-g.
+    assert_eq!("shift-g".parse::<KeyBinding>().unwrap(), "G".parse().unwrap());
+    assert_eq!("G".parse::<KeyBinding>().unwrap().to_string(), "G");
+}
+
+#[test]
+fn invalid_keys_are_rejected() {
+    for text in ["", "ctrl-", "hyper-a", "f13", "f0", "qq", "shift-"] {
+        assert!(text.parse::<KeyBinding>().is_err(), "{text}");
+    }
+    assert!(Config::parse("[keys]\nquit = [\"ctrl-qq\"]").is_err());
+    assert!(Config::parse("[keys]\nfly = [\"f\"]").is_err());
+}
+
+#[test]
+fn vim_defaults() {
+    let keymap = Keymap::default();
+    for (code, action) in [
+        (KeyCode::Char('q'), Action::Quit),
+        (KeyCode::Char('h'), Action::ScrubBack),
+        (KeyCode::Char('l'), Action::ScrubForward),
+        (KeyCode::Char('j'), Action::SelectNext),
+        (KeyCode::Char('k'), Action::SelectPrevious),
+        (KeyCode::Char('G'), Action::GoLive),
+        (KeyCode::Char('?'), Action::ToggleHelp),
+        (KeyCode::Char('/'), Action::FilterProcesses),
+    ] {
+        assert_eq!(keymap.action(key(code)), Some(action), "{code:?}");
+    }
+    // Every action has a key.
+    assert!(Action::ALL.iter().all(|&action| !keymap.keys(action).is_empty()));
+}
+
+#[test]
+fn config_replaces_defaults() {
+    let config = Config::parse("[keys]\nquit = [\"ctrl-q\"]\nexport = [\"q\"]").unwrap();
+    let keymap = Keymap::new(&config.keys).unwrap();
+    let ctrl_q = KeyEvent::new(KeyCode::Char('q'), KeyModifiers::CONTROL);
+    assert_eq!(keymap.action(ctrl_q), Some(Action::Quit));
+    // `q` moved to export, and `x` no longer exports.
+    assert_eq!(keymap.action(key(KeyCode::Char('q'))), Some(Action::Export));
+    assert_eq!(keymap.action(key(KeyCode::Char('x'))), None);
+    assert_eq!(keymap.hint(Action::Quit), "ctrl-q");
+    // Other actions keep their defaults.
+    assert_eq!(keymap.action(key(KeyCode::Char(' '))), Some(Action::TogglePause));
+}
+
+#[test]
+fn conflicting_keys_are_rejected() {
+    let config = Config::parse("[keys]\ntoggle-pause = [\"p\"]\nexport = [\"e\", \"p\"]").unwrap();
+    let err = Keymap::new(&config.keys).unwrap_err();
+    assert_eq!(err, "key `p` is bound to both `toggle-pause` and `export`");
+
+    // Through the config file, as on startup.
+    let path = env::temp_dir().join(format!("mactop-rs-keys-{}.toml", std::process::id()));
+    fs::write(&path, "[keys]\nquit = [\"x\"]\nexport = [\"x\"]").unwrap();
+    let err = Config::load(Some(&path)).unwrap_err().to_string();
+    fs::remove_file(&path).unwrap();
+    assert!(err.contains("`quit` and `export`"), "{err}");
+
+    // The same key twice for one action is no conflict.
+    let config = Config::parse("[keys]\nquit = [\"q\", \"q\"]").unwrap();
+    assert!(Keymap::new(&config.keys).is_ok());
+}
+
+fn app_with_processes() -> App {
+    let mut app = App::default();
+    let mut metrics = Metrics { timestamp: 1_700_000_000.0, ..Default::default() };
+    metrics.processes =
+        [(1, "Safari", 20.0, 5.0), (2, "cc", 90.0, 1.0), (3, "WindowServer", 10.0, 9.0)]
+            .map(|(pid, name, cpu_ms_per_s, energy_impact)| ProcessMetrics {
+                pid,
+                name: name.to_string(),
+                cpu_ms_per_s,
+                energy_impact,
+            })
+            .to_vec();
+    app.on_metrics(0, metrics);
+    app
+}
+
+fn names(app: &App) -> Vec<&str> {
+    app.processes(&app.host().metrics).into_iter().map(|process| process.name.as_str()).collect()
+}
+
+#[test]
+fn sort_processes() {
+    let mut app = app_with_processes();
+    assert_eq!(names(&app), ["WindowServer", "Safari", "cc"]);
+    press(&mut app, "o");
+    assert_eq!(app.process_sort, ProcessSort::Cpu);
+    assert_eq!(names(&app), ["cc", "Safari", "WindowServer"]);
+    press(&mut app, "oo");
+    assert_eq!(names(&app), ["cc", "Safari", "WindowServer"]);
+    press(&mut app, "o");
+    assert_eq!(app.process_sort, ProcessSort::EnergyImpact);
+}
+
+#[test]
+fn filter_processes() {
+    let mut app = app_with_processes();
+    // Keys bound to actions are typed into the filter.
+    press(&mut app, "/sq");
+    handle_key_events(key(KeyCode::Backspace), &mut app).unwrap();
+    press(&mut app, "e");
+    assert_eq!(app.process_filter, "se");
+    handle_key_events(key(KeyCode::Enter), &mut app).unwrap();
+    assert!(app.running && !app.editing_filter);
+    assert_eq!(names(&app), ["WindowServer"]);
+    // Escape clears it before anything else.
+    handle_key_events(key(KeyCode::Esc), &mut app).unwrap();
+    assert!(app.running);
+    assert_eq!(names(&app).len(), 3);
+}
+
+#[test]
+fn export_the_snapshot_shown() {
+    let dir = env::temp_dir().join(format!("mactop-rs-export-{}", std::process::id()));
+    fs::create_dir_all(&dir).unwrap();
+    assert!(App::default().export_to(&dir).is_err());
+
+    let app = app_with_processes();
+    let path = app.export_to(&dir).unwrap();
+    assert_eq!(path.file_name().unwrap(), "mactop-rs-localhost-1700000000.json");
+    let exported: Metrics = serde_json::from_str(&fs::read_to_string(&path).unwrap()).unwrap();
+    assert_eq!(exported.processes.len(), 3);
+    fs::remove_dir_all(dir).unwrap();
+}
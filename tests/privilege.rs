@@ -0,0 +1,47 @@
+//! Runs the root helper and checks the `[privileges]` config.
+
+use std::{
+    io::{BufRead, BufReader},
+    process::{Command, Stdio},
+};
+
+use mactop_rs::{
+    cli::{Args, Mode},
+    config::Config,
+    metrics::{Degraded, Metrics},
+    privilege::Escalate,
+};
+
+#[test]
+fn helper_writes_snapshots_to_stdout() {
+    let mut helper = Command::new(env!("CARGO_BIN_EXE_mactop-rs"))
+        .args(["agent", "--stdio"])
+        .stdout(Stdio::piped())
+        .stderr(Stdio::null())
+        .spawn()
+        .unwrap();
+    let mut line = String::new();
+    BufReader::new(helper.stdout.take().unwrap()).read_line(&mut line).unwrap();
+    helper.kill().unwrap();
+    helper.wait().unwrap();
+
+    let metrics: Metrics = serde_json::from_str(&line).unwrap();
+    if cfg!(not(target_os = "macos")) {
+        assert_eq!(metrics.degraded, Some(Degraded::Missing));
+    }
+}
+
+#[test]
+fn stdio_is_only_an_agent_flag() {
+    let parse = |args: &[&str]| Args::parse(args.iter().map(|arg| arg.to_string()));
+    assert_eq!(parse(&["agent", "--stdio"]).unwrap().unwrap().mode, Mode::Helper);
+    assert!(parse(&["--stdio"]).is_err());
+}
+
+#[test]
+fn escalate_defaults_to_asking() {
+    assert_eq!(Config::parse("").unwrap().privileges.escalate, Escalate::Ask);
+    let config = Config::parse("[privileges]\nescalate = \"never\"").unwrap();
+    assert_eq!(config.privileges.escalate, Escalate::Never);
+    assert!(Config::parse("[privileges]\nescalate = \"always\"").is_err());
+}
@@ -0,0 +1,118 @@
+//! Runs an agent and its client on localhost, and checks the client notices agents going away.
+
+use std::{
+    io::Write,
+    net::{TcpListener, TcpStream},
+    sync::mpsc,
+    thread,
+    time::Duration,
+};
+
+use mactop_rs::{
+    event::Event,
+    metrics::{now, Metrics},
+    remote::{self, ConnectionState, READ_TIMEOUT},
+};
+
+/// Next event of the client, failing the test if none comes in time.
+fn next(events: &mpsc::Receiver<Event>, timeout: Duration) -> Event {
+    events.recv_timeout(timeout).expect("no event from the client")
+}
+
+fn assert_connected(events: &mpsc::Receiver<Event>) {
+    match next(events, Duration::from_secs(10)) {
+        Event::Connection(0, ConnectionState::Connected) => {},
+        event => panic!("expected Connected, got {event:?}"),
+    }
+}
+
+fn assert_metrics(events: &mpsc::Receiver<Event>) {
+    match next(events, Duration::from_secs(10)) {
+        Event::Metrics(0, metrics) => assert!(metrics.timestamp > 0.0),
+        event => panic!("expected Metrics, got {event:?}"),
+    }
+}
+
+fn assert_lost(events: &mpsc::Receiver<Event>, timeout: Duration) -> String {
+    match next(events, timeout) {
+        Event::Connection(0, ConnectionState::Lost(reason)) => reason,
+        event => panic!("expected Lost, got {event:?}"),
+    }
+}
+
+/// Accepts a client and sends it one snapshot.
+fn accept_and_send(listener: &TcpListener) -> TcpStream {
+    let (mut stream, _) = listener.accept().unwrap();
+    let metrics = Metrics { timestamp: now(), ..Default::default() };
+    let line = serde_json::to_string(&metrics).unwrap() + "\n";
+    stream.write_all(line.as_bytes()).unwrap();
+    stream
+}
+
+#[test]
+fn client_receives_agent_snapshots() {
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let addr = listener.local_addr().unwrap().to_string();
+    thread::spawn(move || {
+        let _ = remote::serve_on(listener);
+    });
+
+    let (sender, events) = mpsc::channel();
+    remote::spawn_client(0, addr, sender);
+    assert_connected(&events);
+    assert_metrics(&events);
+    assert_metrics(&events);
+}
+
+#[test]
+fn client_reconnects_after_agent_closes() {
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let addr = listener.local_addr().unwrap().to_string();
+    let (sender, events) = mpsc::channel();
+    remote::spawn_client(0, addr, sender);
+
+    drop(accept_and_send(&listener));
+    assert_connected(&events);
+    assert_metrics(&events);
+    assert_eq!(assert_lost(&events, Duration::from_secs(10)), "connection closed by agent");
+
+    // Back after the backoff.
+    let _stream = accept_and_send(&listener);
+    assert_connected(&events);
+    assert_metrics(&events);
+}
+
+#[test]
+fn client_gives_up_on_silent_agent() {
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let addr = listener.local_addr().unwrap().to_string();
+    let (sender, events) = mpsc::channel();
+    remote::spawn_client(0, addr, sender);
+
+    // Open but silent, as when the agent's machine sleeps or drops off the network.
+    let _stream = accept_and_send(&listener);
+    assert_connected(&events);
+    assert_metrics(&events);
+    let reason = assert_lost(&events, READ_TIMEOUT + Duration::from_secs(5));
+    assert!(reason.starts_with("no snapshot for"), "{reason}");
+
+    let _stream = accept_and_send(&listener);
+    assert_connected(&events);
+    assert_metrics(&events);
+}
+
+#[test]
+fn client_retries_unreachable_agent() {
+    // A port nothing listens on anymore.
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let addr = listener.local_addr().unwrap();
+    drop(listener);
+    let (sender, events) = mpsc::channel();
+    remote::spawn_client(0, addr.to_string(), sender);
+    assert_lost(&events, Duration::from_secs(10));
+
+    let listener = TcpListener::bind(addr).unwrap();
+    let _stream = accept_and_send(&listener);
+    assert_connected(&events);
+    assert_metrics(&events);
+}
@@ -0,0 +1,167 @@
+//! Renders `App`s built from the metric fixtures and compares them with golden buffers.
+//!
+//! Run with `UPDATE_SNAPSHOTS=1` to write the goldens after an intended change, then review the
+//! diff of `tests/snapshots`.
+
+use std::{env, fs, path::PathBuf};
+
+use mactop_rs::{
+    app::App,
+    config::Config,
+    layout::Tab,
+    metrics::{Degraded, Metrics},
+    ui,
+};
+use ratatui::{backend::TestBackend, Terminal};
+
+/// Terminal sizes every fixture is rendered at, the smallest one in the compact layout.
+const SIZES: [(u16, u16); 3] = [(60, 20), (80, 24), (160, 48)];
+
+/// Snapshots of history fed to every app, one per second.
+const SAMPLES: usize = 90;
+
+fn fixture(name: &str) -> Metrics {
+    let path = PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+        .join("tests/fixtures/metrics")
+        .join(format!("{name}.json"));
+    serde_json::from_str(&fs::read_to_string(path).unwrap()).unwrap()
+}
+
+/// An app whose history ends with the `name` fixture, power rising up to it so charts have lines.
+fn app(name: &str) -> App {
+    let metrics = fixture(name);
+    let mut app = App::new(Config::default());
+    for index in 0..SAMPLES {
+        let scale = (index + 1) as f64 / SAMPLES as f64;
+        let mut sample = metrics.clone();
+        sample.timestamp = metrics.timestamp - (SAMPLES - 1 - index) as f64;
+        let cpu = &mut sample.cpu_metrics;
+        (cpu.cpu_w, cpu.gpu_w, cpu.ane_w) =
+            (cpu.cpu_w * scale, cpu.gpu_w * scale, cpu.ane_w * scale);
+        cpu.package_w = cpu.cpu_w + cpu.gpu_w + cpu.ane_w;
+        app.on_metrics(0, sample);
+    }
+    app
+}
+
+/// The symbols of every cell, one line per row without trailing spaces.
+fn render(app: &mut App, width: u16, height: u16) -> String {
+    let mut terminal = Terminal::new(TestBackend::new(width, height)).unwrap();
+    terminal.draw(|frame| ui::render(app, frame)).unwrap();
+    let buffer = terminal.backend().buffer();
+    let mut text = String::new();
+    for y in 0..height {
+        let line: String = (0..width).map(|x| buffer.get(x, y).symbol()).collect();
+        text.push_str(line.trim_end());
+        text.push('\n');
+    }
+    text
+}
+
+/// Compares `actual` with `tests/snapshots/{name}.txt`, or writes it with `UPDATE_SNAPSHOTS`.
+fn assert_snapshot(name: &str, actual: &str) {
+    let path = PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+        .join("tests/snapshots")
+        .join(format!("{name}.txt"));
+    if env::var_os("UPDATE_SNAPSHOTS").is_some() {
+        fs::create_dir_all(path.parent().unwrap()).unwrap();
+        fs::write(&path, actual).unwrap();
+        return;
+    }
+    let expected = fs::read_to_string(&path)
+        .unwrap_or_else(|_| panic!("missing {}, run with UPDATE_SNAPSHOTS=1", path.display()));
+    assert!(
+        expected == actual,
+        "{name} differs from {}, run with UPDATE_SNAPSHOTS=1 to update it\n\
+         --- expected\n{expected}--- actual\n{actual}",
+        path.display()
+    );
+}
+
+fn assert_fixture(name: &str) {
+    let mut app = app(name);
+    for (width, height) in SIZES {
+        assert_snapshot(&format!("{name}_{width}x{height}"), &render(&mut app, width, height));
+    }
+}
+
+#[test]
+fn idle_m1() {
+    assert_fixture("idle_m1");
+}
+
+#[test]
+fn loaded_m2_max() {
+    assert_fixture("loaded_m2_max");
+}
+
+#[test]
+fn unknown_clusters() {
+    assert_fixture("unknown_clusters");
+}
+
+#[test]
+fn empty_soc_info() {
+    assert_fixture("empty_soc_info");
+}
+
+#[test]
+fn tabs() {
+    let mut app = app("loaded_m2_max");
+    for tab in Tab::ALL.into_iter().skip(1) {
+        app.select_tab(tab);
+        let name = format!("tab_{}", tab.title().to_lowercase().replace('/', "_"));
+        assert_snapshot(&name, &render(&mut app, 120, 40));
+    }
+}
+
+#[test]
+fn overlays() {
+    let mut app = app("loaded_m2_max");
+    app.toggle_help();
+    assert_snapshot("overlay_help", &render(&mut app, 120, 40));
+    app.toggle_stats();
+    assert_snapshot("overlay_stats", &render(&mut app, 120, 40));
+    app.toggle_energy();
+    assert_snapshot("overlay_energy", &render(&mut app, 120, 40));
+}
+
+#[test]
+fn not_root() {
+    let mut app = app("not_root_m1");
+    assert!(app.show_degraded, "the first degraded snapshot explains why");
+    assert_snapshot("not_root_explained", &render(&mut app, 100, 30));
+    app.dismiss_degraded();
+    assert_snapshot("not_root_100x30", &render(&mut app, 100, 30));
+    assert_snapshot("not_root_60x20", &render(&mut app, 60, 20));
+}
+
+#[test]
+fn helper_failed() {
+    let mut app = App::new(Config::default());
+    let mut metrics = fixture("not_root_m1");
+    let error = "exit status: 1: sudo: a password is required".to_string();
+    metrics.degraded = Some(Degraded::HelperFailed(error));
+    app.on_metrics(0, metrics);
+    assert!(app.show_degraded);
+    assert_snapshot("helper_failed_explained", &render(&mut app, 100, 30));
+}
+
+#[test]
+fn empty_app() {
+    assert_snapshot("empty_80x24", &render(&mut App::default(), 80, 24));
+}
+
+#[test]
+fn tiny_terminals_do_not_panic() {
+    for name in ["idle_m1", "loaded_m2_max", "unknown_clusters", "empty_soc_info", "not_root_m1"] {
+        let mut app = app(name);
+        for tab in Tab::ALL {
+            app.select_tab(tab);
+            for (width, height) in [(0, 0), (1, 1), (10, 3), (20, 5), (40, 10), (79, 23), (80, 24)]
+            {
+                render(&mut app, width, height);
+            }
+        }
+    }
+}
@@ -0,0 +1,66 @@
+//! Reads the machine info from the `/proc` and `/sys` trees in `tests/fixtures/soc`.
+
+use std::path::PathBuf;
+
+use mactop_rs::soc::SocInfo;
+
+fn linux(name: &str) -> SocInfo {
+    SocInfo::from_linux(
+        &PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures/soc").join(name),
+    )
+}
+
+#[test]
+fn single_kind_of_core() {
+    assert_eq!(
+        linux("intel_laptop"),
+        SocInfo {
+            model: "Intel(R) Core(TM) i5-8250U CPU @ 1.60GHz".to_string(),
+            core_count: 8,
+            e_core_count: 0,
+            p_core_count: 8,
+            gpu_core_count: None,
+            memory_bytes: 8038064 * 1024,
+            os_version: "Ubuntu 22.04.4 LTS".to_string(),
+        }
+    );
+}
+
+#[test]
+fn hybrid_cores_by_frequency() {
+    // Favored P-cores boosting higher are still P-cores.
+    let info = linux("intel_hybrid");
+    assert_eq!((info.core_count, info.e_core_count, info.p_core_count), (16, 8, 8));
+    assert_eq!(info.os_version, "Fedora Linux 40 (Workstation Edition)");
+}
+
+#[test]
+fn apple_silicon_under_asahi() {
+    // No online list, no model name and no os-release.
+    assert_eq!(
+        linux("asahi_m1"),
+        SocInfo {
+            model: "Apple MacBook Air (M1, 2020)".to_string(),
+            core_count: 8,
+            e_core_count: 4,
+            p_core_count: 4,
+            gpu_core_count: None,
+            memory_bytes: 7902196 * 1024,
+            os_version: "Linux 6.8.9-400.asahi.fc40.aarch64+16k".to_string(),
+        }
+    );
+}
+
+#[test]
+fn only_cpuinfo() {
+    assert_eq!(
+        linux("container"),
+        SocInfo {
+            model: "AMD EPYC 7B13".to_string(),
+            core_count: 2,
+            p_core_count: 2,
+            ..Default::default()
+        }
+    );
+    assert_eq!(linux("missing"), SocInfo::default());
+}
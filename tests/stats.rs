@@ -0,0 +1,98 @@
+//! Statistics over a window of samples, and the summaries charts draw of long windows.
+
+use mactop_rs::{
+    history::{Sample, Series},
+    stats::{ChartWindow, Stats, StatsWindow},
+};
+
+/// A sample every second from `0` to `seconds - 1` seconds, valued `value(time)`.
+fn series(seconds: usize, value: impl Fn(f64) -> f64) -> Series {
+    let mut series = Series::new(seconds.max(1));
+    for time in 0..seconds {
+        let time = time as f64;
+        series.push(Sample { time, value: value(time) });
+    }
+    series
+}
+
+#[test]
+fn p95_of_known_values() {
+    // 1 to 100 in any order: the nearest-rank 95th percentile is the 95th value.
+    let stats = Stats::of((1..=100).rev().map(f64::from)).unwrap();
+    assert_eq!(
+        (stats.min, stats.mean, stats.max, stats.p95, stats.count),
+        (1.0, 50.5, 100.0, 95.0, 100)
+    );
+
+    // Few values: the rank rounds up, to the maximum of three values.
+    assert_eq!(Stats::of([3.0, 1.0, 2.0]).unwrap().p95, 3.0);
+    assert_eq!(Stats::of([7.0]).unwrap().p95, 7.0);
+
+    // Values that aren't numbers are left out.
+    let stats = Stats::of([1.0, f64::NAN, 3.0, f64::INFINITY]).unwrap();
+    assert_eq!((stats.count, stats.mean), (2, 2.0));
+}
+
+#[test]
+fn empty_window() {
+    assert_eq!(Stats::of([]), None);
+    assert_eq!(Stats::of([f64::NAN]), None);
+    assert_eq!(Series::new(10).stats(StatsWindow::Seconds30, 100.0), None);
+    // Every sample is after the end of the window.
+    assert_eq!(series(10, |time| time).stats(StatsWindow::Seconds30, -1.0), None);
+}
+
+#[test]
+fn window_longer_than_the_history() {
+    let short = series(20, |time| time);
+    // The hour covers the 20 samples kept.
+    let stats = short.stats(StatsWindow::Hour1, 19.0).unwrap();
+    assert_eq!((stats.count, stats.min, stats.max), (20, 0.0, 19.0));
+
+    // Windows end at the cursor and start at most their length before it.
+    let stats = short.stats(StatsWindow::Seconds30, 10.0).unwrap();
+    assert_eq!((stats.count, stats.max), (11, 10.0));
+    let stats = series(100, |time| time).stats(StatsWindow::Seconds30, 99.0).unwrap();
+    assert_eq!((stats.count, stats.min), (31, 69.0));
+}
+
+#[test]
+fn downsampling_keeps_spikes() {
+    // An hour at 5, but for a single one second spike at 80.
+    let series = series(3600, |time| if time == 1234.0 { 80.0 } else { 5.0 });
+    let buckets = series.downsample(3600.0, 3599.0, 100);
+    assert_eq!(buckets.len(), 100);
+    let spike: Vec<_> = buckets.iter().filter(|bucket| bucket.max > 5.0).collect();
+    assert_eq!(spike.len(), 1);
+    assert_eq!((spike[0].min, spike[0].max), (5.0, 80.0));
+    // The mean smooths the spike out, the envelope doesn't.
+    assert!(spike[0].mean < 10.0);
+    assert!(buckets.iter().all(|bucket| bucket.min == 5.0));
+    assert!(spike[0].time > 1199.0 && spike[0].time < 1271.0);
+}
+
+#[test]
+fn downsampling_skips_empty_slices() {
+    let series = series(10, |time| time);
+    // The first 50 of 60 seconds have no samples.
+    let buckets = series.downsample(60.0, 10.0, 60);
+    assert_eq!(buckets.len(), 10);
+    // More slices than samples keeps every sample.
+    assert!(buckets.iter().all(|bucket| bucket.min == bucket.max));
+    assert!(Series::new(1).downsample(60.0, 0.0, 0).is_empty());
+}
+
+#[test]
+fn zoom_stops_at_its_bounds() {
+    assert_eq!(ChartWindow::Minute1.zoom_in(), ChartWindow::Minute1);
+    assert_eq!(ChartWindow::Minute1.zoom_out(), ChartWindow::Minutes5);
+    assert_eq!(ChartWindow::Minutes15.zoom_in(), ChartWindow::Minutes5);
+    assert_eq!(ChartWindow::Hour1.zoom_out(), ChartWindow::Hour1);
+    assert_eq!(ChartWindow::Hour1.zoom_in(), ChartWindow::Minutes15);
+
+    let mut window = ChartWindow::default();
+    for _ in 0..10 {
+        window = window.zoom_out();
+    }
+    assert_eq!(window.seconds(), 3600.0);
+}
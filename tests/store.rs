@@ -0,0 +1,101 @@
+//! Opens the on-disk history, which must never keep the monitor from starting.
+
+use std::{env, fs, path::PathBuf};
+
+use mactop_rs::{
+    app::App,
+    config::{Config, StoreConfig},
+    metrics::{now, Metrics},
+    store::Store,
+};
+
+/// An empty directory for `name` under the temporary directory.
+fn temp_dir(name: &str) -> PathBuf {
+    let dir = env::temp_dir().join(format!("mactop-rs-{name}-{}", std::process::id()));
+    let _ = fs::remove_dir_all(&dir);
+    fs::create_dir_all(&dir).unwrap();
+    dir
+}
+
+fn app(dir: PathBuf) -> App {
+    App::new(Config {
+        store: StoreConfig { enabled: true, dir: Some(dir), ..Default::default() },
+        ..Default::default()
+    })
+}
+
+#[test]
+fn unusable_directory_runs_without_store() {
+    // A file where the directory should be.
+    let file = temp_dir("unusable").join("history");
+    fs::write(&file, "").unwrap();
+    let mut app = app(file);
+    let errors = app.open_stores();
+    assert_eq!(errors.len(), 1, "{errors:?}");
+    assert!(errors[0].contains("localhost"));
+    assert!(app.host().store.is_none());
+    assert!(app.host().store_error.is_some());
+
+    app.on_metrics(0, Metrics { timestamp: now(), ..Default::default() });
+    assert!(app.host().has_metrics());
+}
+
+#[test]
+fn write_error_is_kept_on_the_host() {
+    let dir = temp_dir("write");
+    let mut app = app(dir.clone());
+    assert!(app.open_stores().is_empty());
+    assert!(app.host().store_error.is_none());
+
+    fs::remove_dir_all(&dir).unwrap();
+    app.on_metrics(0, Metrics { timestamp: now(), ..Default::default() });
+    let host = app.host();
+    assert!(host.store.is_none());
+    assert!(host.store_error.is_some());
+    assert!(host.has_metrics());
+}
+
+#[test]
+fn store_is_opt_in() {
+    let dir = temp_dir("disabled");
+    let mut app = App::new(Config {
+        store: StoreConfig { dir: Some(dir.clone()), ..Default::default() },
+        ..Default::default()
+    });
+    assert!(app.open_stores().is_empty());
+    assert!(app.host().store.is_none());
+    app.on_metrics(0, Metrics { timestamp: now(), ..Default::default() });
+    assert_eq!(fs::read_dir(&dir).unwrap().count(), 0);
+
+    let config = Config::parse("[store]\nenabled = true").unwrap();
+    assert!(config.store.enabled);
+}
+
+#[test]
+fn tilde_is_the_home_directory() {
+    let home = PathBuf::from(env::var_os("HOME").unwrap());
+    let config = |dir: &str| StoreConfig { dir: Some(PathBuf::from(dir)), ..Default::default() };
+    assert_eq!(config("~/history").dir(), Some(home.join("history")));
+    assert_eq!(config("/var/history").dir(), Some(PathBuf::from("/var/history")));
+    assert_eq!(config("~user/history").dir(), Some(PathBuf::from("~user/history")));
+}
+
+#[test]
+fn reloaded_history_is_not_live() {
+    let dir = temp_dir("reload");
+    let config = StoreConfig { dir: Some(dir.clone()), ..Default::default() };
+    let mut store = Store::open(&config, "localhost").unwrap();
+    let start = now() - 120.0;
+    for second in 0..10 {
+        store.append(&Metrics { timestamp: start + second as f64, ..Default::default() }).unwrap();
+    }
+    drop(store);
+
+    let mut app = app(dir);
+    assert!(app.open_stores().is_empty());
+    let host = app.host();
+    assert_eq!(host.history.len(), 10);
+    // The gauges wait for a real sample.
+    assert!(!host.has_metrics());
+    assert_eq!(host.metrics.timestamp, 0.0);
+}